@@ -92,6 +92,8 @@ pub const GCRYCTL_GET_TAGLEN: gcry_ctl_cmds = 76;
 pub const GCRYCTL_REINIT_SYSCALL_CLAMP: gcry_ctl_cmds = 77;
 pub const GCRYCTL_AUTO_EXPAND_SECMEM: gcry_ctl_cmds = 78;
 pub const GCRYCTL_SET_ALLOW_WEAK_KEY: gcry_ctl_cmds = 79;
+pub const GCRYCTL_SET_DECRYPTION_TAG: gcry_ctl_cmds = 80;
+pub const GCRYCTL_MD_CUSTOMIZE: gcry_ctl_cmds = 88;
 
 pub type gcry_sexp_format = c_uint;
 pub const GCRYSEXP_FMT_DEFAULT: gcry_sexp_format = 0;
@@ -148,6 +150,9 @@ pub const GCRY_CIPHER_GOST28147: gcry_cipher_algos = 315;
 pub const GCRY_CIPHER_CHACHA20: gcry_cipher_algos = 316;
 pub const GCRY_CIPHER_GOST28147_MESH: gcry_cipher_algos = 317;
 pub const GCRY_CIPHER_SM4: gcry_cipher_algos = 318;
+pub const GCRY_CIPHER_ARIA128: gcry_cipher_algos = 319;
+pub const GCRY_CIPHER_ARIA192: gcry_cipher_algos = 320;
+pub const GCRY_CIPHER_ARIA256: gcry_cipher_algos = 321;
 
 pub const GCRY_CIPHER_AES128: gcry_cipher_algos = GCRY_CIPHER_AES;
 pub const GCRY_CIPHER_RIJNDAEL: gcry_cipher_algos = GCRY_CIPHER_AES;
@@ -171,12 +176,15 @@ pub const GCRY_CIPHER_MODE_OCB: gcry_cipher_modes = 11;
 pub const GCRY_CIPHER_MODE_CFB8: gcry_cipher_modes = 12;
 pub const GCRY_CIPHER_MODE_XTS: gcry_cipher_modes = 13;
 pub const GCRY_CIPHER_MODE_EAX: gcry_cipher_modes = 14;
+pub const GCRY_CIPHER_MODE_SIV: gcry_cipher_modes = 15;
+pub const GCRY_CIPHER_MODE_GCM_SIV: gcry_cipher_modes = 16;
 
 pub type gcry_cipher_flags = c_uint;
 pub const GCRY_CIPHER_SECURE: gcry_cipher_flags = 1;
 pub const GCRY_CIPHER_ENABLE_SYNC: gcry_cipher_flags = 2;
 pub const GCRY_CIPHER_CBC_CTS: gcry_cipher_flags = 4;
 pub const GCRY_CIPHER_CBC_MAC: gcry_cipher_flags = 8;
+pub const GCRY_CIPHER_EXTENDED: gcry_cipher_flags = 16;
 
 pub const GCRY_GCM_BLOCK_LEN: c_uint = (128 / 8);
 pub const GCRY_CCM_BLOCK_LEN: c_uint = (128 / 8);
@@ -247,6 +255,8 @@ pub const GCRY_MD_BLAKE2S_128: gcry_md_algos = 325;
 pub const GCRY_MD_SM3: gcry_md_algos = 326;
 pub const GCRY_MD_SHA512_256: gcry_md_algos = 327;
 pub const GCRY_MD_SHA512_224: gcry_md_algos = 328;
+pub const GCRY_MD_CSHAKE128: gcry_md_algos = 329;
+pub const GCRY_MD_CSHAKE256: gcry_md_algos = 330;
 
 pub type gcry_md_flags = c_uint;
 pub const GCRY_MD_FLAG_SECURE: gcry_md_flags = 1;
@@ -300,12 +310,15 @@ pub const GCRY_MAC_CMAC_RFC2268: gcry_mac_algos = 209;
 pub const GCRY_MAC_CMAC_IDEA: gcry_mac_algos = 210;
 pub const GCRY_MAC_CMAC_GOST28147: gcry_mac_algos = 211;
 pub const GCRY_MAC_CMAC_SM4: gcry_mac_algos = 212;
+pub const GCRY_MAC_CMAC_ARIA: gcry_mac_algos = 213;
 
 pub const GCRY_MAC_GMAC_AES: gcry_mac_algos = 401;
 pub const GCRY_MAC_GMAC_CAMELLIA: gcry_mac_algos = 402;
 pub const GCRY_MAC_GMAC_TWOFISH: gcry_mac_algos = 403;
 pub const GCRY_MAC_GMAC_SERPENT: gcry_mac_algos = 404;
 pub const GCRY_MAC_GMAC_SEED: gcry_mac_algos = 405;
+pub const GCRY_MAC_GMAC_SM4: gcry_mac_algos = 406;
+pub const GCRY_MAC_GMAC_ARIA: gcry_mac_algos = 407;
 
 pub const GCRY_MAC_POLY1305: gcry_mac_algos = 501;
 pub const GCRY_MAC_POLY1305_AES: gcry_mac_algos = 502;
@@ -313,6 +326,8 @@ pub const GCRY_MAC_POLY1305_CAMELLIA: gcry_mac_algos = 503;
 pub const GCRY_MAC_POLY1305_TWOFISH: gcry_mac_algos = 504;
 pub const GCRY_MAC_POLY1305_SERPENT: gcry_mac_algos = 505;
 pub const GCRY_MAC_POLY1305_SEED: gcry_mac_algos = 506;
+pub const GCRY_MAC_POLY1305_SM4: gcry_mac_algos = 507;
+pub const GCRY_MAC_POLY1305_ARIA: gcry_mac_algos = 508;
 
 pub type gcry_mac_flags = c_uint;
 pub const GCRY_MAC_FLAG_SECURE: gcry_mac_flags = 1;
@@ -325,6 +340,13 @@ pub const GCRY_KDF_ITERSALTED_S2K: gcry_kdf_algos = 19;
 pub const GCRY_KDF_PBKDF1: gcry_kdf_algos = 33;
 pub const GCRY_KDF_PBKDF2: gcry_kdf_algos = 34;
 pub const GCRY_KDF_SCRYPT: gcry_kdf_algos = 48;
+pub const GCRY_KDF_ARGON2: gcry_kdf_algos = 64;
+pub const GCRY_KDF_HKDF: gcry_kdf_algos = 98;
+
+pub type gcry_kdf_subalgo_argon2 = c_uint;
+pub const GCRY_KDF_ARGON2D: gcry_kdf_subalgo_argon2 = 0;
+pub const GCRY_KDF_ARGON2I: gcry_kdf_subalgo_argon2 = 1;
+pub const GCRY_KDF_ARGON2ID: gcry_kdf_subalgo_argon2 = 2;
 
 pub type gcry_random_level = c_uint;
 pub type gcry_random_level_t = gcry_random_level;
@@ -272,6 +272,9 @@ extern "C" {
     pub fn gcry_md_hash_buffer(
         algo: c_int, digest: *mut c_void, buffer: *const c_void, length: size_t,
     );
+    pub fn gcry_md_hash_buffers(
+        algo: c_int, flags: c_uint, digest: *mut c_void, iov: *const gcry_buffer_t, iovcnt: c_int,
+    ) -> gcry_error_t;
     pub fn gcry_md_get_algo(h: gcry_md_hd_t) -> c_int;
     pub fn gcry_md_get_algo_dlen(algo: c_int) -> c_uint;
     pub fn gcry_md_is_enabled(a: gcry_md_hd_t, algo: c_int) -> c_int;
@@ -315,6 +318,15 @@ extern "C" {
         salt: *const c_void, saltlen: size_t, iterations: c_ulong, keysize: size_t,
         keybuffer: *mut c_void,
     ) -> gcry_error_t;
+    pub fn gcry_kdf_open(
+        hd: *mut gcry_kdf_hd_t, algo: c_int, subalgo: c_int, param: *const c_ulong,
+        paramlen: c_uint, passphrase: *const c_void, passphraselen: size_t, salt: *const c_void,
+        saltlen: size_t, key: *const c_void, keylen: size_t, ad: *const c_void, adlen: size_t,
+    ) -> gcry_error_t;
+    pub fn gcry_kdf_compute(h: gcry_kdf_hd_t, ops: *const gcry_kdf_thread_ops_t) -> gcry_error_t;
+    pub fn gcry_kdf_final(h: gcry_kdf_hd_t, resultlen: size_t, result: *mut c_void)
+        -> gcry_error_t;
+    pub fn gcry_kdf_close(h: gcry_kdf_hd_t);
 
     pub fn gcry_randomize(buffer: *mut c_void, length: size_t, level: gcry_random_level_t);
     pub fn gcry_random_add_bytes(
@@ -443,6 +455,17 @@ pub unsafe fn gcry_cipher_set_sbox(h: gcry_cipher_hd_t, oid: *const c_char) -> g
     gcry_cipher_ctl(h, GCRYCTL_SET_SBOX as c_int, oid as *mut _, 0)
 }
 #[inline]
+pub unsafe fn gcry_cipher_set_decryption_tag(
+    h: gcry_cipher_hd_t, tag: *const c_void, taglen: size_t,
+) -> gcry_error_t {
+    gcry_cipher_ctl(
+        h,
+        GCRYCTL_SET_DECRYPTION_TAG as c_int,
+        tag as *mut _,
+        taglen,
+    )
+}
+#[inline]
 pub unsafe fn gcry_cipher_final(h: gcry_cipher_hd_t) -> gcry_error_t {
     gcry_cipher_ctl(h, GCRYCTL_FINALIZE as c_int, ptr::null_mut(), 0)
 }
@@ -45,6 +45,28 @@ pub struct gcry_md_handle {
 pub struct gcry_mac_handle {
     _priv: [u8; 0],
 }
+#[repr(C)]
+pub struct gcry_kdf_handle {
+    _priv: [u8; 0],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct gcry_buffer_t {
+    pub size: size_t,
+    pub off: size_t,
+    pub len: size_t,
+    pub data: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct gcry_cshake_customization {
+    pub n: *const c_void,
+    pub n_len: c_uint,
+    pub s: *const c_void,
+    pub s_len: c_uint,
+}
 
 pub type gcry_ctx_t = *mut gcry_context;
 pub type gcry_sexp_t = *mut gcry_sexp;
@@ -53,6 +75,21 @@ pub type gcry_mpi_point_t = *mut gcry_mpi_point;
 pub type gcry_cipher_hd_t = *mut gcry_cipher_handle;
 pub type gcry_md_hd_t = *mut gcry_md_handle;
 pub type gcry_mac_hd_t = *mut gcry_mac_handle;
+pub type gcry_kdf_hd_t = *mut gcry_kdf_handle;
+
+pub type gcry_kdf_job_fn_t = Option<unsafe extern "C" fn(*mut c_void)>;
+pub type gcry_kdf_dispatch_job_fn_t =
+    Option<unsafe extern "C" fn(*mut c_void, gcry_kdf_job_fn_t, *mut c_void) -> c_int>;
+pub type gcry_kdf_wait_all_jobs_fn_t = Option<unsafe extern "C" fn(*mut c_void) -> c_int>;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct gcry_kdf_thread_ops {
+    pub jobs_context: *mut c_void,
+    pub dispatch_job: gcry_kdf_dispatch_job_fn_t,
+    pub wait_all_jobs: gcry_kdf_wait_all_jobs_fn_t,
+}
+pub type gcry_kdf_thread_ops_t = gcry_kdf_thread_ops;
 
 pub type gcry_prime_check_func_t =
     Option<unsafe extern "C" fn(*mut c_void, c_int, gcry_mpi_t) -> c_int>;
@@ -0,0 +1,191 @@
+use super::{Algorithm, Cipher, Mode};
+use crate::{utils::wipe, Error, Result};
+
+#[cfg(feature = "aead")]
+pub use self::traits::*;
+
+pub(crate) fn tag_len(mode: Mode) -> Result<usize> {
+    match mode {
+        Mode::Ccm
+        | Mode::Gcm
+        | Mode::Poly1305
+        | Mode::Ocb
+        | Mode::Eax
+        | Mode::Siv
+        | Mode::GcmSiv => Ok(16),
+        _ => Err(Error::INV_CIPHER_MODE),
+    }
+}
+
+fn init(
+    algo: Algorithm, mode: Mode, key: &[u8], nonce: &[u8], aad: &[u8], msg_len: usize,
+    tag_len: usize,
+) -> Result<Cipher> {
+    let mut cipher = Cipher::new(algo, mode)?;
+    cipher.set_key(key)?;
+    if mode == Mode::Siv {
+        // The nonce is the last component of the S2V input, so it follows the AAD.
+        cipher.authenticate(aad)?;
+        if !nonce.is_empty() {
+            cipher.set_iv(nonce)?;
+        }
+    } else {
+        cipher.set_iv(nonce)?;
+        if mode == Mode::Ccm {
+            cipher.set_ccm_lengths(msg_len as u64, aad.len() as u64, tag_len)?;
+        }
+        cipher.authenticate(aad)?;
+    }
+    cipher.finalize()?;
+    Ok(cipher)
+}
+
+pub(crate) fn encrypt(
+    algo: Algorithm, mode: Mode, key: &[u8], nonce: &[u8], aad: &[u8], buffer: &mut [u8],
+    tag: &mut [u8],
+) -> Result<()> {
+    let mut cipher = init(algo, mode, key, nonce, aad, buffer.len(), tag.len())?;
+    cipher.encrypt_inplace(buffer)?;
+    cipher.get_tag(tag)
+}
+
+pub(crate) fn decrypt(
+    algo: Algorithm, mode: Mode, key: &[u8], nonce: &[u8], aad: &[u8], buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<()> {
+    let result =
+        init(algo, mode, key, nonce, aad, buffer.len(), tag.len()).and_then(|mut cipher| {
+            // The SIV modes verify the tag while decrypting and need it up front.
+            if (mode == Mode::Siv) || (mode == Mode::GcmSiv) {
+                cipher.set_decryption_tag(tag)?;
+            }
+            cipher.decrypt_inplace(buffer)?;
+            cipher.verify_tag(tag)
+        });
+    // libgcrypt only checks the tag after decrypting, so the unauthenticated plaintext must not
+    // be left behind in the caller's buffer.
+    if result.is_err() {
+        wipe(buffer);
+    }
+    result
+}
+
+#[inline]
+pub fn seal(
+    algo: Algorithm, mode: Mode, key: &[u8], nonce: &[u8], aad: &[u8], plaintext: &[u8],
+) -> Result<Vec<u8>> {
+    let tag_len = tag_len(mode)?;
+    let mut output = Vec::with_capacity(plaintext.len() + tag_len);
+    output.extend_from_slice(plaintext);
+    output.resize(plaintext.len() + tag_len, 0);
+    let (data, tag) = output.split_at_mut(plaintext.len());
+    encrypt(algo, mode, key, nonce, aad, data, tag)?;
+    Ok(output)
+}
+
+#[inline]
+pub fn open(
+    algo: Algorithm, mode: Mode, key: &[u8], nonce: &[u8], aad: &[u8], ciphertext: &[u8],
+) -> Result<Vec<u8>> {
+    let tag_len = tag_len(mode)?;
+    if ciphertext.len() < tag_len {
+        return Err(Error::TOO_SHORT);
+    }
+    let (data, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+    let mut output = data.to_vec();
+    decrypt(algo, mode, key, nonce, aad, &mut output, tag)?;
+    Ok(output)
+}
+
+#[cfg(feature = "aead")]
+mod traits {
+    use std::fmt;
+
+    use ::aead::{
+        consts::{U0, U12, U16, U24, U32},
+        generic_array::GenericArray,
+        AeadCore, AeadInPlace, Error, Key, KeyInit, KeySizeUser, Nonce, Tag,
+    };
+
+    use super::{decrypt, encrypt};
+    use crate::{
+        cipher::{Algorithm, Mode},
+        utils::wipe,
+    };
+
+    macro_rules! impl_aead {
+        ($($Name:ident: $Algo:ident, $Mode:ident, $KeySize:ty, $NonceSize:ty, $TagSize:ty;)+) => {$(
+            #[derive(Clone)]
+            pub struct $Name(GenericArray<u8, $KeySize>);
+
+            impl Drop for $Name {
+                #[inline]
+                fn drop(&mut self) {
+                    wipe(&mut self.0);
+                }
+            }
+
+            impl fmt::Debug for $Name {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.debug_struct(stringify!($Name)).finish()
+                }
+            }
+
+            impl KeySizeUser for $Name {
+                type KeySize = $KeySize;
+            }
+
+            impl KeyInit for $Name {
+                #[inline]
+                fn new(key: &Key<Self>) -> Self {
+                    $Name(key.clone())
+                }
+            }
+
+            impl AeadCore for $Name {
+                type NonceSize = $NonceSize;
+                type TagSize = $TagSize;
+                type CiphertextOverhead = U0;
+            }
+
+            impl AeadInPlace for $Name {
+                fn encrypt_in_place_detached(
+                    &self, nonce: &Nonce<Self>, aad: &[u8], buffer: &mut [u8],
+                ) -> ::aead::Result<Tag<Self>> {
+                    let mut tag = Tag::<Self>::default();
+                    encrypt(
+                        Algorithm::$Algo,
+                        Mode::$Mode,
+                        &self.0,
+                        nonce,
+                        aad,
+                        buffer,
+                        &mut tag,
+                    )
+                    .map_err(|_| Error)?;
+                    Ok(tag)
+                }
+
+                fn decrypt_in_place_detached(
+                    &self, nonce: &Nonce<Self>, aad: &[u8], buffer: &mut [u8], tag: &Tag<Self>,
+                ) -> ::aead::Result<()> {
+                    decrypt(Algorithm::$Algo, Mode::$Mode, &self.0, nonce, aad, buffer, tag)
+                        .map_err(|_| Error)
+                }
+            }
+        )+};
+    }
+
+    impl_aead! {
+        Aes128Gcm:        Aes128,   Gcm,      U16, U12, U16;
+        Aes192Gcm:        Aes192,   Gcm,      U24, U12, U16;
+        Aes256Gcm:        Aes256,   Gcm,      U32, U12, U16;
+        Aes128Ccm:        Aes128,   Ccm,      U16, U12, U16;
+        Aes256Ccm:        Aes256,   Ccm,      U32, U12, U16;
+        Aes128Ocb:        Aes128,   Ocb,      U16, U12, U16;
+        Aes256Ocb:        Aes256,   Ocb,      U32, U12, U16;
+        Aes128Eax:        Aes128,   Eax,      U16, U16, U16;
+        Aes256Eax:        Aes256,   Eax,      U32, U16, U16;
+        ChaCha20Poly1305: Chacha20, Poly1305, U32, U12, U16;
+    }
+}
@@ -0,0 +1,150 @@
+use std::{fmt, marker::PhantomData};
+
+use ::cipher::{
+    consts::{U16, U24, U32, U8},
+    generic_array::ArrayLength,
+    inout::{InOut, InOutBuf},
+    AlgorithmName, Block, BlockBackend, BlockCipher, BlockClosure, BlockDecrypt, BlockEncrypt,
+    BlockSizeUser, Key, KeyInit, KeySizeUser, ParBlocks, ParBlocksSizeUser, Unsigned,
+};
+use ffi;
+use libc::{c_void, size_t};
+
+use super::{Algorithm, Cipher, Mode};
+use crate::{Error, Result};
+
+type Op = unsafe extern "C" fn(
+    ffi::gcry_cipher_hd_t,
+    *mut c_void,
+    size_t,
+    *const c_void,
+    size_t,
+) -> ffi::gcry_error_t;
+
+fn open(algo: Algorithm, key: &[u8]) -> Result<Cipher> {
+    let mut cipher = Cipher::new(algo, Mode::Ecb)?;
+    cipher.set_key(key)?;
+    Ok(cipher)
+}
+
+struct Backend<'a, BS> {
+    cipher: &'a Cipher,
+    op: Op,
+    _size: PhantomData<BS>,
+}
+
+impl<'a, BS: ArrayLength<u8>> Backend<'a, BS> {
+    #[inline]
+    fn new(cipher: &'a Cipher, op: Op) -> Self {
+        Backend {
+            cipher,
+            op,
+            _size: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: *const u8, output: *mut u8, len: usize) {
+        // In ECB mode libgcrypt handles each block independently and accepts identical input and
+        // output buffers, so whole batches can be passed through in a single call.
+        let err = unsafe { (self.op)(self.cipher.as_raw(), output.cast(), len, input.cast(), len) };
+        match Error::new(err) {
+            Error::NO_ERROR => (),
+            err => panic!("block cipher operation failed: {}", err),
+        }
+    }
+}
+
+impl<BS: ArrayLength<u8>> BlockSizeUser for Backend<'_, BS> {
+    type BlockSize = BS;
+}
+
+impl<BS: ArrayLength<u8>> ParBlocksSizeUser for Backend<'_, BS> {
+    type ParBlocksSize = U8;
+}
+
+impl<BS: ArrayLength<u8>> BlockBackend for Backend<'_, BS> {
+    #[inline]
+    fn proc_block(&mut self, block: InOut<'_, '_, Block<Self>>) {
+        let (input, output) = block.into_raw();
+        self.process(input.cast(), output.cast(), BS::USIZE);
+    }
+
+    #[inline]
+    fn proc_par_blocks(&mut self, blocks: InOut<'_, '_, ParBlocks<Self>>) {
+        let (input, output) = blocks.into_raw();
+        self.process(input.cast(), output.cast(), BS::USIZE * U8::USIZE);
+    }
+
+    #[inline]
+    fn proc_tail_blocks(&mut self, blocks: InOutBuf<'_, '_, Block<Self>>) {
+        let len = blocks.len() * BS::USIZE;
+        let (input, output) = blocks.into_raw();
+        self.process(input.cast(), output.cast(), len);
+    }
+}
+
+macro_rules! impl_block_cipher {
+    ($($Name:ident: $Algo:ident, $KeySize:ty, $BlockSize:ty;)+) => {$(
+        #[derive(Debug)]
+        pub struct $Name(Cipher);
+
+        impl KeySizeUser for $Name {
+            type KeySize = $KeySize;
+        }
+
+        impl BlockSizeUser for $Name {
+            type BlockSize = $BlockSize;
+        }
+
+        impl BlockCipher for $Name {}
+
+        impl KeyInit for $Name {
+            #[inline]
+            fn new(key: &Key<Self>) -> Self {
+                $Name(open(Algorithm::$Algo, key).expect("unable to initialize cipher"))
+            }
+        }
+
+        impl AlgorithmName for $Name {
+            fn write_alg_name(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(stringify!($Name))
+            }
+        }
+
+        impl BlockEncrypt for $Name {
+            #[inline]
+            fn encrypt_with_backend(&self, f: impl BlockClosure<BlockSize = Self::BlockSize>) {
+                f.call(&mut Backend::new(&self.0, ffi::gcry_cipher_encrypt));
+            }
+        }
+
+        impl BlockDecrypt for $Name {
+            #[inline]
+            fn decrypt_with_backend(&self, f: impl BlockClosure<BlockSize = Self::BlockSize>) {
+                f.call(&mut Backend::new(&self.0, ffi::gcry_cipher_decrypt));
+            }
+        }
+    )+};
+}
+
+impl_block_cipher! {
+    Aes128:      Aes128,      U16, U16;
+    Aes192:      Aes192,      U24, U16;
+    Aes256:      Aes256,      U32, U16;
+    Camellia128: Camellia128, U16, U16;
+    Camellia192: Camellia192, U24, U16;
+    Camellia256: Camellia256, U32, U16;
+    Serpent128:  Serpent128,  U16, U16;
+    Serpent192:  Serpent192,  U24, U16;
+    Serpent256:  Serpent256,  U32, U16;
+    Twofish128:  Twofish128,  U16, U16;
+    Twofish256:  Twofish,     U32, U16;
+    Sm4:         Sm4,         U16, U16;
+    Seed:        Seed,        U16, U16;
+    Cast5:       Cast5,       U16, U8;
+    Idea:        Idea,        U16, U8;
+    Aria128:     Aria128,     U16, U16;
+    Aria192:     Aria192,     U24, U16;
+    Aria256:     Aria256,     U32, U16;
+}
@@ -0,0 +1,231 @@
+use std::{
+    cmp, fmt,
+    io::{self, Read, Write},
+};
+
+use super::{Cipher, Mode, Padding};
+use crate::{Error, Result};
+
+const BUF_SIZE: usize = 8192;
+
+fn block_len(cipher: &Cipher) -> Result<usize> {
+    match cipher.mode() {
+        Some(Mode::Ecb) | Some(Mode::Cbc) => match cipher.algorithm().map(|a| a.block_len()) {
+            Some(0) | None => Err(Error::CIPHER_ALGO),
+            Some(n) => Ok(n),
+        },
+        Some(Mode::Cfb) | Some(Mode::Cfb8) | Some(Mode::Ofb) | Some(Mode::Ctr)
+        | Some(Mode::Stream) => Ok(1),
+        _ => Err(Error::INV_CIPHER_MODE),
+    }
+}
+
+pub struct Encryptor<W: Write> {
+    inner: W,
+    cipher: Cipher,
+    block_len: usize,
+    padding: Option<Padding>,
+    partial: Vec<u8>,
+    out: Vec<u8>,
+}
+
+impl<W: Write + fmt::Debug> fmt::Debug for Encryptor<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encryptor")
+            .field("inner", &self.inner)
+            .field("cipher", &self.cipher)
+            .finish()
+    }
+}
+
+impl<W: Write> Encryptor<W> {
+    #[inline]
+    pub fn new(inner: W, cipher: Cipher) -> Result<Self> {
+        Encryptor::with_padding(inner, cipher, Padding::Pkcs7)
+    }
+
+    #[inline]
+    pub fn with_padding(inner: W, cipher: Cipher, padding: Padding) -> Result<Self> {
+        let block_len = block_len(&cipher)?;
+        Ok(Encryptor {
+            inner,
+            cipher,
+            block_len,
+            padding: Some(padding).filter(|_| block_len > 1),
+            partial: Vec::with_capacity(block_len),
+            out: Vec::new(),
+        })
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    fn dump(&mut self) -> io::Result<()> {
+        while !self.out.is_empty() {
+            match self.inner.write(&self.out) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.out.drain(..n);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.dump()?;
+        if let Some(padding) = self.padding {
+            padding.pad(&mut self.partial, self.block_len);
+            self.out.append(&mut self.partial);
+            self.cipher.encrypt_inplace(&mut self.out)?;
+            self.dump()?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Encryptor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.dump()?;
+        let len = cmp::min(buf.len(), BUF_SIZE);
+        self.partial.extend_from_slice(&buf[..len]);
+        let end = self.partial.len() - (self.partial.len() % self.block_len);
+        self.out.extend(self.partial.drain(..end));
+        if !self.out.is_empty() {
+            self.cipher.encrypt_inplace(&mut self.out)?;
+        }
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.dump()?;
+        self.inner.flush()
+    }
+}
+
+pub struct Decryptor<R: Read> {
+    inner: R,
+    cipher: Cipher,
+    block_len: usize,
+    padding: Option<Padding>,
+    pending: Vec<u8>,
+    out: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: Read + fmt::Debug> fmt::Debug for Decryptor<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Decryptor")
+            .field("inner", &self.inner)
+            .field("cipher", &self.cipher)
+            .finish()
+    }
+}
+
+impl<R: Read> Decryptor<R> {
+    #[inline]
+    pub fn new(inner: R, cipher: Cipher) -> Result<Self> {
+        Decryptor::with_padding(inner, cipher, Padding::Pkcs7)
+    }
+
+    #[inline]
+    pub fn with_padding(inner: R, cipher: Cipher, padding: Padding) -> Result<Self> {
+        let block_len = block_len(&cipher)?;
+        Ok(Decryptor {
+            inner,
+            cipher,
+            block_len,
+            padding: Some(padding).filter(|_| block_len > 1),
+            pending: Vec::new(),
+            out: Vec::new(),
+            pos: 0,
+            eof: false,
+        })
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let start = self.pending.len();
+        self.pending.resize(start + BUF_SIZE, 0);
+        let n = match self.inner.read(&mut self.pending[start..]) {
+            Ok(n) => n,
+            Err(e) => {
+                self.pending.truncate(start);
+                return Err(e);
+            }
+        };
+        self.pending.truncate(start + n);
+
+        let eof = n == 0;
+        let end = if eof {
+            self.pending.len()
+        } else if self.padding.is_some() {
+            // The last block is held back until the input is exhausted so its padding can be
+            // removed.
+            (self.pending.len().saturating_sub(1) / self.block_len) * self.block_len
+        } else {
+            self.pending.len()
+        };
+
+        self.out.clear();
+        self.pos = 0;
+        self.out.extend(self.pending.drain(..end));
+        if !self.out.is_empty() {
+            self.cipher.decrypt_inplace(&mut self.out)?;
+        }
+        if let Some(padding) = self.padding.filter(|_| eof) {
+            match padding.unpad(&self.out, self.block_len) {
+                Ok(len) => self.out.truncate(len),
+                Err(e) => {
+                    self.out.clear();
+                    return Err(e.into());
+                }
+            }
+        }
+        self.eof = eof;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Decryptor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.out.len() {
+                let n = cmp::min(buf.len(), self.out.len() - self.pos);
+                buf[..n].copy_from_slice(&self.out[self.pos..(self.pos + n)]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if self.eof || buf.is_empty() {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+    }
+}
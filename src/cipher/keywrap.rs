@@ -0,0 +1,98 @@
+use super::{Algorithm, Cipher, Flags, Mode};
+use crate::{require_gcrypt_ver, utils::wipe, Error, Result};
+
+const BLOCK_LEN: usize = 8;
+
+fn open(kek: &[u8], flags: Flags) -> Result<Cipher> {
+    let algo = match kek.len() {
+        16 => Algorithm::Aes128,
+        24 => Algorithm::Aes192,
+        32 => Algorithm::Aes256,
+        _ => return Err(Error::INV_KEYLEN),
+    };
+    let mut cipher = Cipher::with_flags(algo, Mode::AesWrap, flags)?;
+    cipher.set_key(kek)?;
+    Ok(cipher)
+}
+
+fn check_len(len: usize, min: usize) -> Result<()> {
+    if (len < min) || ((len / BLOCK_LEN) * BLOCK_LEN != len) {
+        return Err(Error::INV_LENGTH);
+    }
+    Ok(())
+}
+
+fn decrypt(cipher: &mut Cipher, wrapped: &[u8]) -> Result<Vec<u8>> {
+    let mut output = vec![0; wrapped.len() - BLOCK_LEN];
+    match cipher.decrypt(wrapped, &mut output) {
+        Ok(()) => Ok(output),
+        Err(e) => {
+            wipe(&mut output);
+            if e.code() == Error::CHECKSUM.code() {
+                Err(Error::CHECKSUM)
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+pub fn wrap(kek: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+    check_len(key.len(), 2 * BLOCK_LEN)?;
+    let mut output = vec![0; key.len() + BLOCK_LEN];
+    open(kek, Flags::NONE)?.encrypt(key, &mut output)?;
+    Ok(output)
+}
+
+pub fn unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>> {
+    check_len(wrapped.len(), 3 * BLOCK_LEN)?;
+    decrypt(&mut open(kek, Flags::NONE)?, wrapped)
+}
+
+pub fn wrap_pad(kek: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+    require_gcrypt_ver! {
+        (1, 9) => {
+            if key.is_empty() || (key.len() > u32::MAX as usize) {
+                return Err(Error::INV_LENGTH);
+            }
+            let padded = (key.len() + (BLOCK_LEN - 1)) & !(BLOCK_LEN - 1);
+            let mut output = vec![0; padded + BLOCK_LEN];
+            open(kek, Flags::EXTENDED)?.encrypt(key, &mut output)?;
+            Ok(output)
+        } else {
+            let _ = (kek, key);
+            Err(Error::NOT_SUPPORTED)
+        }
+    }
+}
+
+pub fn unwrap_pad(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>> {
+    require_gcrypt_ver! {
+        (1, 9) => {
+            use libc::c_int;
+
+            use crate::error::return_err;
+
+            check_len(wrapped.len(), 2 * BLOCK_LEN)?;
+            let mut cipher = open(kek, Flags::EXTENDED)?;
+            let mut output = decrypt(&mut cipher, wrapped)?;
+            // libgcrypt reports the unpadded length as the big-endian message length indicator from
+            // the integrity check value.
+            let mut mli = [0u8; 4];
+            let mut size = mli.len();
+            unsafe {
+                return_err!(ffi::gcry_cipher_info(
+                    cipher.as_raw(),
+                    ffi::GCRYCTL_GET_KEYLEN as c_int,
+                    mli.as_mut_ptr().cast(),
+                    &mut size
+                ));
+            }
+            output.truncate(u32::from_be_bytes(mli) as usize);
+            Ok(output)
+        } else {
+            let _ = (kek, wrapped);
+            Err(Error::NOT_SUPPORTED)
+        }
+    }
+}
@@ -1,11 +1,25 @@
-use std::{ffi::CStr, ptr, result, str::Utf8Error};
+use std::{convert::TryFrom, ffi::CStr, mem, ptr, result, str::Utf8Error};
 
 use bitflags::bitflags;
 use cstr_argument::CStrArgument;
 use ffi;
 use libc::c_int;
 
-use crate::{error::return_err, NonNull, Result};
+use crate::{error::return_err, Error, NonNull, Result};
+
+pub mod aead;
+#[cfg(feature = "cipher")]
+pub mod block;
+mod io;
+pub mod keywrap;
+pub mod padding;
+pub mod siv;
+pub mod xts;
+
+pub use self::{
+    io::{Decryptor, Encryptor},
+    padding::Padding,
+};
 
 ffi_enum_wrapper! {
     #[allow(non_camel_case_types)]
@@ -43,6 +57,9 @@ ffi_enum_wrapper! {
         Chacha20         = ffi::GCRY_CIPHER_CHACHA20,
         Gost28147Mesh    = ffi::GCRY_CIPHER_GOST28147_MESH,
         Sm4              = ffi::GCRY_CIPHER_SM4,
+        Aria128          = ffi::GCRY_CIPHER_ARIA128,
+        Aria192          = ffi::GCRY_CIPHER_ARIA192,
+        Aria256          = ffi::GCRY_CIPHER_ARIA256,
     }
 }
 
@@ -106,6 +123,8 @@ ffi_enum_wrapper! {
         Cfb8     = ffi::GCRY_CIPHER_MODE_CFB8,
         Xts      = ffi::GCRY_CIPHER_MODE_XTS,
         Eax      = ffi::GCRY_CIPHER_MODE_EAX,
+        Siv      = ffi::GCRY_CIPHER_MODE_SIV,
+        GcmSiv   = ffi::GCRY_CIPHER_MODE_GCM_SIV,
     }
 }
 
@@ -129,11 +148,17 @@ bitflags! {
         const ENABLE_SYNC = ffi::GCRY_CIPHER_ENABLE_SYNC;
         const CBC_CTS     = ffi::GCRY_CIPHER_CBC_CTS;
         const CBC_MAC     = ffi::GCRY_CIPHER_CBC_MAC;
+        const EXTENDED    = ffi::GCRY_CIPHER_EXTENDED;
     }
 }
 
 #[derive(Debug)]
-pub struct Cipher(NonNull<ffi::gcry_cipher_hd_t>);
+pub struct Cipher {
+    raw: NonNull<ffi::gcry_cipher_hd_t>,
+    // libgcrypt cannot report these for a handle.
+    algo: Option<Algorithm>,
+    mode: Option<Mode>,
+}
 
 impl Drop for Cipher {
     #[inline]
@@ -145,7 +170,32 @@ impl Drop for Cipher {
 }
 
 impl Cipher {
-    impl_wrapper!(Cipher: ffi::gcry_cipher_hd_t);
+    /// Takes ownership of an open handle. Its algorithm and mode are unknown, so checks that
+    /// depend on them are left to libgcrypt.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a valid handle that is not owned elsewhere.
+    #[inline]
+    pub unsafe fn from_raw(raw: ffi::gcry_cipher_hd_t) -> Self {
+        Cipher {
+            raw: NonNull::<ffi::gcry_cipher_hd_t>::new(raw).unwrap(),
+            algo: None,
+            mode: None,
+        }
+    }
+
+    #[inline]
+    pub fn as_raw(&self) -> ffi::gcry_cipher_hd_t {
+        self.raw.as_ptr()
+    }
+
+    #[inline]
+    pub fn into_raw(self) -> ffi::gcry_cipher_hd_t {
+        let raw = self.as_raw();
+        mem::forget(self);
+        raw
+    }
 
     #[inline]
     pub fn new(algo: Algorithm, mode: Mode) -> Result<Cipher> {
@@ -163,7 +213,27 @@ impl Cipher {
                 mode.raw(),
                 flags.bits()
             ));
-            Ok(Cipher::from_raw(handle))
+            let mut cipher = Cipher::from_raw(handle);
+            cipher.algo = Some(algo);
+            cipher.mode = Some(mode);
+            Ok(cipher)
+        }
+    }
+
+    #[inline]
+    pub fn algorithm(&self) -> Option<Algorithm> {
+        self.algo
+    }
+
+    #[inline]
+    pub fn mode(&self) -> Option<Mode> {
+        self.mode
+    }
+
+    fn check_mode(&self, valid: impl FnOnce(Mode) -> bool) -> Result<()> {
+        match self.mode {
+            Some(mode) if !valid(mode) => Err(Error::INV_CIPHER_MODE),
+            _ => Ok(()),
         }
     }
 
@@ -214,6 +284,95 @@ impl Cipher {
         Ok(())
     }
 
+    #[inline]
+    pub fn allow_weak_key(&mut self, allow: bool) -> Result<()> {
+        unsafe {
+            return_err!(ffi::gcry_cipher_ctl(
+                self.as_raw(),
+                ffi::GCRYCTL_SET_ALLOW_WEAK_KEY as c_int,
+                ptr::null_mut(),
+                allow.into()
+            ));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_sbox(&mut self, oid: impl CStrArgument) -> Result<()> {
+        let oid = oid.into_cstr();
+        unsafe {
+            return_err!(ffi::gcry_cipher_set_sbox(
+                self.as_raw(),
+                oid.as_ref().as_ptr()
+            ));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_ccm_lengths(&mut self, msg_len: u64, aad_len: u64, tag_len: usize) -> Result<()> {
+        self.check_mode(|m| m == Mode::Ccm)?;
+        let mut lengths = [msg_len, aad_len, tag_len as u64];
+        unsafe {
+            return_err!(ffi::gcry_cipher_ctl(
+                self.as_raw(),
+                ffi::GCRYCTL_SET_CCM_LENGTHS as c_int,
+                lengths.as_mut_ptr().cast(),
+                mem::size_of_val(&lengths)
+            ));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_tag_len(&mut self, len: usize) -> Result<()> {
+        self.check_mode(|m| m == Mode::Ocb)?;
+        let mut len = c_int::try_from(len).map_err(|_| Error::INV_LENGTH)?;
+        unsafe {
+            return_err!(ffi::gcry_cipher_ctl(
+                self.as_raw(),
+                ffi::GCRYCTL_SET_TAGLEN as c_int,
+                (&mut len as *mut c_int).cast(),
+                mem::size_of_val(&len)
+            ));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn tag_len(&self) -> Result<usize> {
+        self.check_mode(|m| {
+            matches!(
+                m,
+                Mode::Ccm
+                    | Mode::Gcm
+                    | Mode::Poly1305
+                    | Mode::Ocb
+                    | Mode::Eax
+                    | Mode::Siv
+                    | Mode::GcmSiv
+            )
+        })?;
+        let mut len = 0;
+        unsafe {
+            return_err!(ffi::gcry_cipher_info(
+                self.as_raw(),
+                ffi::GCRYCTL_GET_TAGLEN as c_int,
+                ptr::null_mut(),
+                &mut len
+            ));
+        }
+        Ok(len)
+    }
+
+    #[inline]
+    pub fn finalize(&mut self) -> Result<()> {
+        unsafe {
+            return_err!(ffi::gcry_cipher_final(self.as_raw()));
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn authenticate(&mut self, bytes: &[u8]) -> Result<()> {
         unsafe {
@@ -250,6 +409,18 @@ impl Cipher {
         Ok(())
     }
 
+    #[inline]
+    pub fn set_decryption_tag(&mut self, tag: &[u8]) -> Result<()> {
+        unsafe {
+            return_err!(ffi::gcry_cipher_set_decryption_tag(
+                self.as_raw(),
+                tag.as_ptr().cast(),
+                tag.len()
+            ));
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn encrypt(&mut self, input: &[u8], output: &mut [u8]) -> Result<()> {
         unsafe {
@@ -305,4 +476,29 @@ impl Cipher {
         }
         Ok(())
     }
+
+    fn block_len(&self) -> Result<usize> {
+        match self.algo.map(|a| a.block_len()) {
+            Some(0) | None => Err(Error::CIPHER_ALGO),
+            Some(n) => Ok(n),
+        }
+    }
+
+    pub fn encrypt_padded(&mut self, padding: Padding, input: &[u8]) -> Result<Vec<u8>> {
+        let block_len = self.block_len()?;
+        let mut output = Vec::with_capacity(input.len() + block_len);
+        output.extend_from_slice(input);
+        padding.pad(&mut output, block_len);
+        self.encrypt_inplace(&mut output)?;
+        Ok(output)
+    }
+
+    pub fn decrypt_padded(&mut self, padding: Padding, input: &[u8]) -> Result<Vec<u8>> {
+        let block_len = self.block_len()?;
+        let mut output = input.to_vec();
+        self.decrypt_inplace(&mut output)?;
+        let len = padding.unpad(&output, block_len)?;
+        output.truncate(len);
+        Ok(output)
+    }
 }
@@ -0,0 +1,85 @@
+use crate::{Error, Result};
+
+#[inline]
+fn mask(cond: bool) -> usize {
+    0usize.wrapping_sub(usize::from(cond))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Padding {
+    Pkcs7,
+    Iso7816,
+    AnsiX923,
+    Zero,
+}
+
+impl Padding {
+    pub fn pad(self, buf: &mut Vec<u8>, block_len: usize) {
+        assert!((block_len > 0) && (block_len < 256));
+        let len = buf.len();
+        let n = block_len - (len % block_len);
+        match self {
+            Padding::Pkcs7 => buf.resize(len + n, n as u8),
+            Padding::Iso7816 => {
+                buf.push(0x80);
+                buf.resize(len + n, 0);
+            }
+            Padding::AnsiX923 => {
+                buf.resize(len + n - 1, 0);
+                buf.push(n as u8);
+            }
+            Padding::Zero => {
+                if n != block_len {
+                    buf.resize(len + n, 0);
+                }
+            }
+        }
+    }
+
+    pub fn unpad(self, buf: &[u8], block_len: usize) -> Result<usize> {
+        assert!((block_len > 0) && (block_len < 256));
+        let rem = buf.len() % block_len;
+        if (rem > 0) || (buf.is_empty() && (self != Padding::Zero)) {
+            return Err(Error::DECRYPT_FAILED);
+        } else if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Every byte of the final block is examined regardless of where the padding ends so that
+        // the time taken does not depend on its contents.
+        let block = &buf[(buf.len() - block_len)..];
+        let mut n = 0;
+        let mut bad = false;
+        match self {
+            Padding::Pkcs7 | Padding::AnsiX923 => {
+                n = usize::from(block[block_len - 1]);
+                bad = (n == 0) | (n > block_len);
+                let fill = if self == Padding::Pkcs7 { n as u8 } else { 0 };
+                for (i, &b) in block.iter().rev().enumerate().skip(1) {
+                    bad |= (i < n) & (b != fill);
+                }
+            }
+            Padding::Iso7816 => {
+                let mut found = false;
+                for (i, &b) in block.iter().rev().enumerate() {
+                    let first = !found & (b != 0);
+                    bad |= first & (b != 0x80);
+                    n |= (i + 1) & mask(first);
+                    found |= first;
+                }
+                bad |= !found;
+            }
+            Padding::Zero => {
+                let mut found = false;
+                for &b in block.iter().rev() {
+                    found |= b != 0;
+                    n += usize::from(!found);
+                }
+            }
+        }
+        if bad {
+            return Err(Error::DECRYPT_FAILED);
+        }
+        Ok(buf.len() - n)
+    }
+}
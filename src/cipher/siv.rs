@@ -0,0 +1,47 @@
+use super::{Algorithm, Cipher, Mode};
+use crate::{require_gcrypt_ver, utils::wipe, Error, Result};
+
+pub const TAG_LEN: usize = 16;
+
+fn init(algo: Algorithm, key: &[u8], aad: &[&[u8]]) -> Result<Cipher> {
+    require_gcrypt_ver! {
+        (1, 10) => {
+            let mut cipher = Cipher::new(algo, Mode::Siv)?;
+            cipher.set_key(key)?;
+            for component in aad {
+                cipher.authenticate(component)?;
+            }
+            Ok(cipher)
+        } else {
+            let _ = (algo, key, aad);
+            Err(Error::NOT_SUPPORTED)
+        }
+    }
+}
+
+pub fn seal(algo: Algorithm, key: &[u8], aad: &[&[u8]], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut cipher = init(algo, key, aad)?;
+    let mut output = vec![0; TAG_LEN + plaintext.len()];
+    let (tag, data) = output.split_at_mut(TAG_LEN);
+    data.copy_from_slice(plaintext);
+    cipher.encrypt_inplace(data)?;
+    cipher.get_tag(tag)?;
+    Ok(output)
+}
+
+pub fn open(algo: Algorithm, key: &[u8], aad: &[&[u8]], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if ciphertext.len() < TAG_LEN {
+        return Err(Error::TOO_SHORT);
+    }
+    let (tag, data) = ciphertext.split_at(TAG_LEN);
+    let mut output = data.to_vec();
+    let result = init(algo, key, aad).and_then(|mut cipher| {
+        cipher.set_decryption_tag(tag)?;
+        cipher.decrypt_inplace(&mut output)
+    });
+    if let Err(e) = result {
+        wipe(&mut output);
+        return Err(e);
+    }
+    Ok(output)
+}
@@ -0,0 +1,69 @@
+use super::{Algorithm, Cipher, Mode};
+use crate::{Error, Result};
+
+#[derive(Debug)]
+pub struct SectorCipher {
+    cipher: Cipher,
+    sector_size: usize,
+}
+
+impl SectorCipher {
+    pub fn new(algo: Algorithm, key: &[u8], sector_size: usize) -> Result<SectorCipher> {
+        let block_len = algo.block_len();
+        if (block_len == 0) || (sector_size < block_len) {
+            return Err(Error::INV_ARG);
+        }
+        let rem = sector_size % block_len;
+        if rem > 0 {
+            return Err(Error::INV_ARG);
+        }
+        if key.len() != (2 * algo.key_len()) {
+            return Err(Error::INV_KEYLEN);
+        }
+        let (key1, key2) = key.split_at(algo.key_len());
+        if key1 == key2 {
+            return Err(Error::WEAK_KEY);
+        }
+        let mut cipher = Cipher::new(algo, Mode::Xts)?;
+        cipher.set_key(key)?;
+        Ok(SectorCipher {
+            cipher,
+            sector_size,
+        })
+    }
+
+    #[inline]
+    pub fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    #[inline]
+    pub fn encrypt_sectors(&mut self, sector: u64, data: &mut [u8]) -> Result<()> {
+        self.process(sector, data, Cipher::encrypt_inplace)
+    }
+
+    #[inline]
+    pub fn decrypt_sectors(&mut self, sector: u64, data: &mut [u8]) -> Result<()> {
+        self.process(sector, data, Cipher::decrypt_inplace)
+    }
+
+    fn process(
+        &mut self, sector: u64, data: &mut [u8], op: fn(&mut Cipher, &mut [u8]) -> Result<()>,
+    ) -> Result<()> {
+        let count = (data.len() / self.sector_size) as u64;
+        if (count as usize * self.sector_size) != data.len() {
+            return Err(Error::INV_LENGTH);
+        }
+        if (count > 0) && sector.checked_add(count - 1).is_none() {
+            return Err(Error::INV_ARG);
+        }
+        for (i, chunk) in data.chunks_mut(self.sector_size).enumerate() {
+            // plain64: the sector index as a little-endian integer, zero-extended to the block.
+            let mut tweak = [0u8; 16];
+            tweak[..8].copy_from_slice(&(sector + i as u64).to_le_bytes());
+            self.cipher.set_iv(tweak)?;
+            op(&mut self.cipher, chunk)?;
+        }
+        Ok(())
+    }
+}
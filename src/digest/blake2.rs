@@ -0,0 +1,102 @@
+use std::fmt;
+
+use super::{Algorithm, Digest, MessageDigest};
+use crate::{utils::ct_eq, Error, Result};
+
+/// BLAKE2b or BLAKE2s with an optional key, in which case the result is a MAC as described in
+/// RFC 7693. The output length selects the matching `Algorithm` variant.
+#[derive(Copy, Clone)]
+pub struct Blake2<'a> {
+    wide: bool,
+    key: &'a [u8],
+    output_len: usize,
+}
+
+impl fmt::Debug for Blake2<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blake2")
+            .field("wide", &self.wide)
+            .field("output_len", &self.output_len)
+            .finish()
+    }
+}
+
+impl<'a> Blake2<'a> {
+    #[inline]
+    pub fn blake2b() -> Blake2<'a> {
+        Blake2 {
+            wide: true,
+            key: &[],
+            output_len: 64,
+        }
+    }
+
+    #[inline]
+    pub fn blake2s() -> Blake2<'a> {
+        Blake2 {
+            wide: false,
+            key: &[],
+            output_len: 32,
+        }
+    }
+
+    #[inline]
+    pub fn key(&mut self, key: &'a [u8]) -> &mut Self {
+        self.key = key;
+        self
+    }
+
+    #[inline]
+    pub fn output_len(&mut self, len: usize) -> &mut Self {
+        self.output_len = len;
+        self
+    }
+
+    #[inline]
+    pub fn max_key_len(&self) -> usize {
+        if self.wide {
+            64
+        } else {
+            32
+        }
+    }
+
+    pub fn algorithm(&self) -> Result<Algorithm> {
+        match (self.wide, self.output_len) {
+            (true, 20) => Ok(Algorithm::Blake2B160),
+            (true, 32) => Ok(Algorithm::Blake2B256),
+            (true, 48) => Ok(Algorithm::Blake2B384),
+            (true, 64) => Ok(Algorithm::Blake2B512),
+            (false, 16) => Ok(Algorithm::Blake2S128),
+            (false, 20) => Ok(Algorithm::Blake2S160),
+            (false, 28) => Ok(Algorithm::Blake2S224),
+            (false, 32) => Ok(Algorithm::Blake2S256),
+            _ => Err(Error::INV_LENGTH),
+        }
+    }
+
+    pub fn open(&self) -> Result<MessageDigest> {
+        let algo = self.algorithm()?;
+        if self.key.len() > self.max_key_len() {
+            return Err(Error::INV_KEYLEN);
+        }
+        let mut md = MessageDigest::new(algo)?;
+        if !self.key.is_empty() {
+            md.set_key(self.key)?;
+        }
+        Ok(md)
+    }
+
+    pub fn hash(&self, data: &[u8]) -> Result<Digest> {
+        let mut md = self.open()?;
+        md.update(data);
+        md.read_digest(self.algorithm()?)
+    }
+
+    pub fn verify(&self, data: &[u8], tag: &[u8]) -> Result<()> {
+        if !ct_eq(self.hash(data)?.as_bytes(), tag) {
+            return Err(Error::CHECKSUM);
+        }
+        Ok(())
+    }
+}
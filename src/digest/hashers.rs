@@ -0,0 +1,175 @@
+use ::digest::{
+    consts::{U104, U128, U136, U144, U16, U168, U20, U28, U32, U48, U64, U72},
+    ExtendableOutput, ExtendableOutputReset, FixedOutput, FixedOutputReset, HashMarker,
+    OutputSizeUser, Reset, Update,
+};
+
+use super::{Algorithm, MessageDigest, XofReader};
+
+fn open(algo: Algorithm) -> MessageDigest {
+    MessageDigest::new(algo).expect("unable to initialize digest")
+}
+
+fn read(md: &mut MessageDigest, algo: Algorithm, out: &mut [u8]) {
+    md.finish();
+    let digest = md.get_digest(algo).expect("unable to read digest");
+    out.copy_from_slice(digest);
+}
+
+macro_rules! impl_hasher {
+    ($($Name:ident: $Algo:ident, $OutputSize:ty, $BlockSize:ty;)+) => {$(
+        #[derive(Debug)]
+        pub struct $Name(MessageDigest);
+
+        impl Default for $Name {
+            #[inline]
+            fn default() -> Self {
+                $Name(open(Algorithm::$Algo))
+            }
+        }
+
+        impl Clone for $Name {
+            #[inline]
+            fn clone(&self) -> Self {
+                $Name(self.0.try_clone().expect("unable to copy digest"))
+            }
+        }
+
+        impl HashMarker for $Name {}
+
+        impl OutputSizeUser for $Name {
+            type OutputSize = $OutputSize;
+        }
+
+        impl ::digest::core_api::BlockSizeUser for $Name {
+            type BlockSize = $BlockSize;
+        }
+
+        impl Update for $Name {
+            #[inline]
+            fn update(&mut self, data: &[u8]) {
+                self.0.update(data);
+            }
+        }
+
+        impl Reset for $Name {
+            #[inline]
+            fn reset(&mut self) {
+                self.0.reset();
+            }
+        }
+
+        impl FixedOutput for $Name {
+            #[inline]
+            fn finalize_into(mut self, out: &mut ::digest::Output<Self>) {
+                read(&mut self.0, Algorithm::$Algo, out);
+            }
+        }
+
+        impl FixedOutputReset for $Name {
+            #[inline]
+            fn finalize_into_reset(&mut self, out: &mut ::digest::Output<Self>) {
+                read(&mut self.0, Algorithm::$Algo, out);
+                self.0.reset();
+            }
+        }
+    )+};
+}
+
+impl_hasher! {
+    Md5:        Md5,        U16, U64;
+    Sha1:       Sha1,       U20, U64;
+    Rmd160:     Rmd160,     U20, U64;
+    Sha224:     Sha224,     U28, U64;
+    Sha256:     Sha256,     U32, U64;
+    Sha384:     Sha384,     U48, U128;
+    Sha512:     Sha512,     U64, U128;
+    Sha512_224: Sha512_224, U28, U128;
+    Sha512_256: Sha512_256, U32, U128;
+    Sha3_224:   Sha3_224,   U28, U144;
+    Sha3_256:   Sha3_256,   U32, U136;
+    Sha3_384:   Sha3_384,   U48, U104;
+    Sha3_512:   Sha3_512,   U64, U72;
+    Blake2b256: Blake2B256, U32, U128;
+    Blake2b384: Blake2B384, U48, U128;
+    Blake2b512: Blake2B512, U64, U128;
+    Blake2s128: Blake2S128, U16, U64;
+    Blake2s256: Blake2S256, U32, U64;
+    Stribog256: Stribog256, U32, U64;
+    Stribog512: Stribog512, U64, U64;
+    Whirlpool:  Whirlpool,  U64, U64;
+    Sm3:        Sm3,        U32, U64;
+}
+
+impl ::digest::XofReader for XofReader {
+    #[inline]
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.get_mut()
+            .extract(buffer)
+            .expect("unable to extract output");
+    }
+}
+
+macro_rules! impl_xof {
+    ($($Name:ident: $Algo:ident, $BlockSize:ty;)+) => {$(
+        #[derive(Debug)]
+        pub struct $Name(MessageDigest);
+
+        impl Default for $Name {
+            #[inline]
+            fn default() -> Self {
+                $Name(open(Algorithm::$Algo))
+            }
+        }
+
+        impl Clone for $Name {
+            #[inline]
+            fn clone(&self) -> Self {
+                $Name(self.0.try_clone().expect("unable to copy digest"))
+            }
+        }
+
+        impl HashMarker for $Name {}
+
+        impl ::digest::core_api::BlockSizeUser for $Name {
+            type BlockSize = $BlockSize;
+        }
+
+        impl Update for $Name {
+            #[inline]
+            fn update(&mut self, data: &[u8]) {
+                self.0.update(data);
+            }
+        }
+
+        impl Reset for $Name {
+            #[inline]
+            fn reset(&mut self) {
+                self.0.reset();
+            }
+        }
+
+        impl ExtendableOutput for $Name {
+            type Reader = XofReader;
+
+            #[inline]
+            fn finalize_xof(self) -> XofReader {
+                self.0.into_xof_reader()
+            }
+        }
+
+        impl ExtendableOutputReset for $Name {
+            #[inline]
+            fn finalize_xof_reset(&mut self) -> XofReader {
+                let reader = self.clone().finalize_xof();
+                self.0.reset();
+                reader
+            }
+        }
+    )+};
+}
+
+impl_xof! {
+    Shake128: Shake128, U168;
+    Shake256: Shake256, U136;
+}
@@ -0,0 +1,274 @@
+use super::{Algorithm, MessageDigest};
+use crate::{utils::ct_eq, Error, Result};
+
+// Domain separation prefixes from RFC 6962, section 2.1.
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+struct Hasher {
+    md: MessageDigest,
+    algo: Algorithm,
+}
+
+impl Hasher {
+    fn new(algo: Algorithm) -> Result<Hasher> {
+        if algo.digest_len() == 0 {
+            return Err(Error::DIGEST_ALGO);
+        }
+        Ok(Hasher {
+            md: MessageDigest::new(algo)?,
+            algo,
+        })
+    }
+
+    fn hash(&mut self, parts: &[&[u8]]) -> Result<Vec<u8>> {
+        self.md.reset();
+        for part in parts {
+            self.md.update(part);
+        }
+        Ok(self.md.read_digest(self.algo)?.into_bytes())
+    }
+
+    fn leaf(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        self.hash(&[&[LEAF_PREFIX], data])
+    }
+
+    fn node(&mut self, left: &[u8], right: &[u8]) -> Result<Vec<u8>> {
+        self.hash(&[&[NODE_PREFIX], left, right])
+    }
+
+    fn root(&mut self, leaves: &[Vec<u8>]) -> Result<Vec<u8>> {
+        match leaves.len() {
+            0 => self.hash(&[]),
+            1 => Ok(leaves[0].clone()),
+            n => {
+                let k = split(n);
+                let left = self.root(&leaves[..k])?;
+                let right = self.root(&leaves[k..])?;
+                self.node(&left, &right)
+            }
+        }
+    }
+
+    fn path(&mut self, index: usize, leaves: &[Vec<u8>], proof: &mut Vec<Vec<u8>>) -> Result<()> {
+        let n = leaves.len();
+        if n <= 1 {
+            return Ok(());
+        }
+        let k = split(n);
+        if index < k {
+            self.path(index, &leaves[..k], proof)?;
+            proof.push(self.root(&leaves[k..])?);
+        } else {
+            self.path(index - k, &leaves[k..], proof)?;
+            proof.push(self.root(&leaves[..k])?);
+        }
+        Ok(())
+    }
+
+    fn subproof(
+        &mut self, m: usize, leaves: &[Vec<u8>], complete: bool, proof: &mut Vec<Vec<u8>>,
+    ) -> Result<()> {
+        let n = leaves.len();
+        if m == n {
+            if !complete {
+                proof.push(self.root(leaves)?);
+            }
+            return Ok(());
+        }
+        let k = split(n);
+        if m <= k {
+            self.subproof(m, &leaves[..k], complete, proof)?;
+            proof.push(self.root(&leaves[k..])?);
+        } else {
+            self.subproof(m - k, &leaves[k..], false, proof)?;
+            proof.push(self.root(&leaves[..k])?);
+        }
+        Ok(())
+    }
+}
+
+// The largest power of two smaller than `n`, for `n > 1`.
+fn split(n: usize) -> usize {
+    let mut k = 1;
+    while (k << 1) < n {
+        k <<= 1;
+    }
+    k
+}
+
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    algo: Algorithm,
+    leaves: Vec<Vec<u8>>,
+}
+
+impl MerkleTree {
+    #[inline]
+    pub fn new(algo: Algorithm) -> Result<MerkleTree> {
+        Hasher::new(algo)?;
+        Ok(MerkleTree {
+            algo,
+            leaves: Vec::new(),
+        })
+    }
+
+    #[inline]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algo
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    #[inline]
+    pub fn leaf_hash(&self, index: usize) -> Option<&[u8]> {
+        self.leaves.get(index).map(|x| &x[..])
+    }
+
+    pub fn push(&mut self, leaf: &[u8]) -> Result<()> {
+        let hash = Hasher::new(self.algo)?.leaf(leaf)?;
+        self.leaves.push(hash);
+        Ok(())
+    }
+
+    pub fn extend<I>(&mut self, leaves: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut hasher = Hasher::new(self.algo)?;
+        for leaf in leaves {
+            self.leaves.push(hasher.leaf(leaf.as_ref())?);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn root(&self) -> Result<Vec<u8>> {
+        self.root_at(self.len())
+    }
+
+    pub fn root_at(&self, size: usize) -> Result<Vec<u8>> {
+        let leaves = self.leaves.get(..size).ok_or(Error::INV_ARG)?;
+        Hasher::new(self.algo)?.root(leaves)
+    }
+
+    pub fn inclusion_proof(&self, index: usize, size: usize) -> Result<Vec<Vec<u8>>> {
+        let leaves = self.leaves.get(..size).ok_or(Error::INV_ARG)?;
+        if index >= size {
+            return Err(Error::INV_ARG);
+        }
+        let mut proof = Vec::new();
+        Hasher::new(self.algo)?.path(index, leaves, &mut proof)?;
+        Ok(proof)
+    }
+
+    pub fn consistency_proof(&self, old_size: usize, new_size: usize) -> Result<Vec<Vec<u8>>> {
+        let leaves = self.leaves.get(..new_size).ok_or(Error::INV_ARG)?;
+        if (old_size == 0) || (old_size > new_size) {
+            return Err(Error::INV_ARG);
+        }
+        let mut proof = Vec::new();
+        if old_size < new_size {
+            Hasher::new(self.algo)?.subproof(old_size, leaves, true, &mut proof)?;
+        }
+        Ok(proof)
+    }
+}
+
+#[inline]
+pub fn leaf_hash(algo: Algorithm, leaf: &[u8]) -> Result<Vec<u8>> {
+    Hasher::new(algo)?.leaf(leaf)
+}
+
+pub fn verify_inclusion<P: AsRef<[u8]>>(
+    algo: Algorithm, leaf_hash: &[u8], index: u64, size: u64, proof: &[P], root: &[u8],
+) -> Result<()> {
+    if index >= size {
+        return Err(Error::INV_ARG);
+    }
+    let mut hasher = Hasher::new(algo)?;
+    let mut f = index;
+    let mut s = size - 1;
+    let mut r = leaf_hash.to_vec();
+    for p in proof {
+        if s == 0 {
+            return Err(Error::CHECKSUM);
+        }
+        if ((f & 1) == 1) || (f == s) {
+            r = hasher.node(p.as_ref(), &r)?;
+            while ((f & 1) == 0) && (f != 0) {
+                f >>= 1;
+                s >>= 1;
+            }
+        } else {
+            r = hasher.node(&r, p.as_ref())?;
+        }
+        f >>= 1;
+        s >>= 1;
+    }
+    if (s != 0) || !ct_eq(&r, root) {
+        return Err(Error::CHECKSUM);
+    }
+    Ok(())
+}
+
+pub fn verify_consistency<P: AsRef<[u8]>>(
+    algo: Algorithm, old_size: u64, new_size: u64, old_root: &[u8], new_root: &[u8], proof: &[P],
+) -> Result<()> {
+    if (old_size == 0) || (old_size > new_size) {
+        return Err(Error::INV_ARG);
+    }
+    if old_size == new_size {
+        if !proof.is_empty() || !ct_eq(old_root, new_root) {
+            return Err(Error::CHECKSUM);
+        }
+        return Ok(());
+    }
+    let mut hasher = Hasher::new(algo)?;
+    let mut proof = proof.iter().map(|p| p.as_ref());
+    // When the old tree is a complete subtree its root is the first node of the path and is
+    // omitted from the proof.
+    let first = if old_size.is_power_of_two() {
+        old_root
+    } else {
+        proof.next().ok_or(Error::CHECKSUM)?
+    };
+    let mut f = old_size - 1;
+    let mut s = new_size - 1;
+    while (f & 1) == 1 {
+        f >>= 1;
+        s >>= 1;
+    }
+    let mut fr = first.to_vec();
+    let mut sr = first.to_vec();
+    for c in proof {
+        if s == 0 {
+            return Err(Error::CHECKSUM);
+        }
+        if ((f & 1) == 1) || (f == s) {
+            fr = hasher.node(c, &fr)?;
+            sr = hasher.node(c, &sr)?;
+            while ((f & 1) == 0) && (f != 0) {
+                f >>= 1;
+                s >>= 1;
+            }
+        } else {
+            sr = hasher.node(&sr, c)?;
+        }
+        f >>= 1;
+        s >>= 1;
+    }
+    if (s != 0) || !ct_eq(&fr, old_root) || !ct_eq(&sr, new_root) {
+        return Err(Error::CHECKSUM);
+    }
+    Ok(())
+}
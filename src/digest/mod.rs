@@ -0,0 +1,546 @@
+use std::{
+    convert::TryFrom,
+    ffi::CStr,
+    io::{self, IoSlice, Read, Write},
+    ptr, result, slice,
+    str::Utf8Error,
+};
+
+use bitflags::bitflags;
+use cstr_argument::CStrArgument;
+use ffi;
+use libc::c_int;
+
+use crate::{error::return_err, require_gcrypt_ver, utils::ct_eq, Error, NonNull, Result};
+
+mod blake2;
+#[cfg(feature = "digest")]
+mod hashers;
+pub mod merkle;
+mod multi;
+pub mod sp800_185;
+mod stream;
+
+pub use self::blake2::Blake2;
+#[cfg(feature = "digest")]
+pub use self::hashers::*;
+pub use self::multi::{Digests, MultiDigest};
+pub use self::stream::{hash_file, hash_reader, HashingReader, HashingWriter};
+
+ffi_enum_wrapper! {
+    #[allow(non_camel_case_types)]
+    pub enum Algorithm: c_int {
+        Md5           = ffi::GCRY_MD_MD5,
+        Sha1          = ffi::GCRY_MD_SHA1,
+        Rmd160        = ffi::GCRY_MD_RMD160,
+        Md2           = ffi::GCRY_MD_MD2,
+        Tiger         = ffi::GCRY_MD_TIGER,
+        Haval         = ffi::GCRY_MD_HAVAL,
+        Sha256        = ffi::GCRY_MD_SHA256,
+        Sha384        = ffi::GCRY_MD_SHA384,
+        Sha512        = ffi::GCRY_MD_SHA512,
+        Sha224        = ffi::GCRY_MD_SHA224,
+        Md4           = ffi::GCRY_MD_MD4,
+        Crc32         = ffi::GCRY_MD_CRC32,
+        Crc32Rfc1510  = ffi::GCRY_MD_CRC32_RFC1510,
+        Crc24Rfc2440  = ffi::GCRY_MD_CRC24_RFC2440,
+        Whirlpool     = ffi::GCRY_MD_WHIRLPOOL,
+        Tiger1        = ffi::GCRY_MD_TIGER1,
+        Tiger2        = ffi::GCRY_MD_TIGER2,
+        GostR3411_94  = ffi::GCRY_MD_GOSTR3411_94,
+        Stribog256    = ffi::GCRY_MD_STRIBOG256,
+        Stribog512    = ffi::GCRY_MD_STRIBOG512,
+        Gostr3411Cp   = ffi::GCRY_MD_GOSTR3411_CP,
+        Sha3_224      = ffi::GCRY_MD_SHA3_224,
+        Sha3_256      = ffi::GCRY_MD_SHA3_256,
+        Sha3_384      = ffi::GCRY_MD_SHA3_384,
+        Sha3_512      = ffi::GCRY_MD_SHA3_512,
+        Shake128      = ffi::GCRY_MD_SHAKE128,
+        Shake256      = ffi::GCRY_MD_SHAKE256,
+        Blake2B512    = ffi::GCRY_MD_BLAKE2B_512,
+        Blake2B384    = ffi::GCRY_MD_BLAKE2B_384,
+        Blake2B256    = ffi::GCRY_MD_BLAKE2B_256,
+        Blake2B160    = ffi::GCRY_MD_BLAKE2B_160,
+        Blake2S256    = ffi::GCRY_MD_BLAKE2S_256,
+        Blake2S224    = ffi::GCRY_MD_BLAKE2S_224,
+        Blake2S160    = ffi::GCRY_MD_BLAKE2S_160,
+        Blake2S128    = ffi::GCRY_MD_BLAKE2S_128,
+        Sm3           = ffi::GCRY_MD_SM3,
+        Sha512_256    = ffi::GCRY_MD_SHA512_256,
+        Sha512_224    = ffi::GCRY_MD_SHA512_224,
+        CShake128     = ffi::GCRY_MD_CSHAKE128,
+        CShake256     = ffi::GCRY_MD_CSHAKE256,
+    }
+}
+
+impl Algorithm {
+    #[inline]
+    pub fn from_name(name: impl CStrArgument) -> Option<Algorithm> {
+        let name = name.into_cstr();
+        let result = unsafe { ffi::gcry_md_map_name(name.as_ref().as_ptr()) };
+        if result != 0 {
+            unsafe { Some(Algorithm::from_raw(result)) }
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        let _ = crate::init_default();
+        unsafe { ffi::gcry_md_test_algo(self.raw()) == 0 }
+    }
+
+    #[inline]
+    pub fn name(&self) -> result::Result<&'static str, Option<Utf8Error>> {
+        self.name_raw()
+            .map_or(Err(None), |s| s.to_str().map_err(Some))
+    }
+
+    #[inline]
+    pub fn name_raw(&self) -> Option<&'static CStr> {
+        unsafe {
+            ffi::gcry_md_algo_name(self.raw())
+                .as_ref()
+                .map(|s| CStr::from_ptr(s))
+        }
+    }
+
+    #[inline]
+    pub fn from_oid(oid: &str) -> Option<Algorithm> {
+        let valid = !oid.is_empty()
+            && oid
+                .split('.')
+                .all(|x| !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit()));
+        if !valid {
+            return None;
+        }
+        Algorithm::from_name(format!("oid.{}", oid))
+    }
+
+    #[inline]
+    pub fn digest_len(&self) -> usize {
+        unsafe { ffi::gcry_md_get_algo_dlen(self.raw()) as usize }
+    }
+
+    pub fn block_len(&self) -> usize {
+        match *self {
+            Algorithm::Md2 => 16,
+            Algorithm::GostR3411_94 | Algorithm::Gostr3411Cp => 32,
+            Algorithm::Md4
+            | Algorithm::Md5
+            | Algorithm::Sha1
+            | Algorithm::Rmd160
+            | Algorithm::Tiger
+            | Algorithm::Tiger1
+            | Algorithm::Tiger2
+            | Algorithm::Sha224
+            | Algorithm::Sha256
+            | Algorithm::Whirlpool
+            | Algorithm::Stribog256
+            | Algorithm::Stribog512
+            | Algorithm::Blake2S128
+            | Algorithm::Blake2S160
+            | Algorithm::Blake2S224
+            | Algorithm::Blake2S256
+            | Algorithm::Sm3 => 64,
+            Algorithm::Sha3_512 => 72,
+            Algorithm::Sha3_384 => 104,
+            Algorithm::Haval
+            | Algorithm::Sha384
+            | Algorithm::Sha512
+            | Algorithm::Sha512_224
+            | Algorithm::Sha512_256
+            | Algorithm::Blake2B160
+            | Algorithm::Blake2B256
+            | Algorithm::Blake2B384
+            | Algorithm::Blake2B512 => 128,
+            Algorithm::Sha3_256 | Algorithm::Shake256 | Algorithm::CShake256 => 136,
+            Algorithm::Sha3_224 => 144,
+            Algorithm::Shake128 | Algorithm::CShake128 => 168,
+            _ => 0,
+        }
+    }
+
+    #[inline]
+    pub fn is_xof(&self) -> bool {
+        matches!(
+            *self,
+            Algorithm::Shake128 | Algorithm::Shake256 | Algorithm::CShake128 | Algorithm::CShake256
+        )
+    }
+
+    pub fn asn_oid_prefix(&self) -> Option<Vec<u8>> {
+        let _ = crate::init_default();
+        unsafe {
+            let mut len = 0;
+            if (ffi::gcry_md_get_asnoid(self.raw(), ptr::null_mut(), &mut len) != 0) || (len == 0) {
+                return None;
+            }
+            let mut buf = vec![0u8; len];
+            if ffi::gcry_md_get_asnoid(self.raw(), buf.as_mut_ptr().cast(), &mut len) != 0 {
+                return None;
+            }
+            buf.truncate(len);
+            Some(buf)
+        }
+    }
+
+    pub fn oid(&self) -> Option<String> {
+        // DigestInfo ::= SEQUENCE { SEQUENCE { OBJECT IDENTIFIER, NULL }, OCTET STRING }
+        let prefix = self.asn_oid_prefix()?;
+        if (prefix.first() != Some(&0x30)) || (prefix.get(2) != Some(&0x30)) {
+            return None;
+        }
+        let (&tag, rest) = prefix.get(4..)?.split_first()?;
+        let (&len, rest) = rest.split_first()?;
+        if (tag != 0x06) || (len >= 0x80) {
+            return None;
+        }
+        let body = rest.get(..usize::from(len))?;
+        match body.last() {
+            Some(&b) if (b & 0x80) == 0 => (),
+            _ => return None,
+        }
+        let mut ids = Vec::new();
+        let mut value = 0u64;
+        for &b in body {
+            value = value.checked_mul(128)? | u64::from(b & 0x7f);
+            if (b & 0x80) == 0 {
+                ids.push(value);
+                value = 0;
+            }
+        }
+        let first = *ids.first()?;
+        let (x, y) = match first {
+            0..=39 => (0, first),
+            40..=79 => (1, first - 40),
+            _ => (2, first - 80),
+        };
+        let mut result = format!("{}.{}", x, y);
+        for id in &ids[1..] {
+            result.push_str(&format!(".{}", id));
+        }
+        Some(result)
+    }
+}
+
+bitflags! {
+    pub struct Flags: ffi::gcry_md_flags {
+        const NONE   = 0;
+        const SECURE  = ffi::GCRY_MD_FLAG_SECURE;
+        const HMAC    = ffi::GCRY_MD_FLAG_HMAC;
+        const BUGEMU1 = ffi::GCRY_MD_FLAG_BUGEMU1;
+    }
+}
+
+#[derive(Debug)]
+pub struct MessageDigest(NonNull<ffi::gcry_md_hd_t>);
+
+impl Drop for MessageDigest {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            ffi::gcry_md_close(self.as_raw());
+        }
+    }
+}
+
+impl MessageDigest {
+    impl_wrapper!(MessageDigest: ffi::gcry_md_hd_t);
+
+    #[inline]
+    pub fn new(algo: Algorithm) -> Result<MessageDigest> {
+        MessageDigest::with_flags(algo, Flags::NONE)
+    }
+
+    #[inline]
+    pub fn with_flags(algo: Algorithm, flags: Flags) -> Result<MessageDigest> {
+        let _ = crate::init_default();
+        unsafe {
+            let mut handle: ffi::gcry_md_hd_t = ptr::null_mut();
+            return_err!(ffi::gcry_md_open(&mut handle, algo.raw(), flags.bits()));
+            Ok(MessageDigest::from_raw(handle))
+        }
+    }
+
+    #[inline]
+    pub fn try_clone(&self) -> Result<MessageDigest> {
+        let mut handle: ffi::gcry_md_hd_t = ptr::null_mut();
+        unsafe {
+            return_err!(ffi::gcry_md_copy(&mut handle, self.as_raw()));
+            Ok(MessageDigest::from_raw(handle))
+        }
+    }
+
+    #[inline]
+    pub fn enable(&mut self, algo: Algorithm) -> Result<()> {
+        unsafe {
+            return_err!(ffi::gcry_md_enable(self.as_raw(), algo.raw()));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_enabled(&self, algo: Algorithm) -> bool {
+        unsafe { ffi::gcry_md_is_enabled(self.as_raw(), algo.raw()) != 0 }
+    }
+
+    #[inline]
+    pub fn is_secure(&self) -> bool {
+        unsafe { ffi::gcry_md_is_secure(self.as_raw()) != 0 }
+    }
+
+    #[inline]
+    pub fn set_key(&mut self, key: impl AsRef<[u8]>) -> Result<()> {
+        let key = key.as_ref();
+        unsafe {
+            return_err!(ffi::gcry_md_setkey(
+                self.as_raw(),
+                key.as_ptr().cast(),
+                key.len()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn customize(&mut self, name: &[u8], custom: &[u8]) -> Result<()> {
+        require_gcrypt_ver! {
+            (1, 11) => {
+                let n_len = libc::c_uint::try_from(name.len()).map_err(|_| Error::INV_LENGTH)?;
+                let s_len = libc::c_uint::try_from(custom.len()).map_err(|_| Error::INV_LENGTH)?;
+                let mut params = ffi::gcry_cshake_customization {
+                    n: name.as_ptr().cast(),
+                    n_len,
+                    s: custom.as_ptr().cast(),
+                    s_len,
+                };
+                unsafe {
+                    return_err!(ffi::gcry_md_ctl(
+                        self.as_raw(),
+                        ffi::GCRYCTL_MD_CUSTOMIZE as c_int,
+                        (&mut params as *mut ffi::gcry_cshake_customization).cast(),
+                        std::mem::size_of_val(&params)
+                    ));
+                }
+                Ok(())
+            } else {
+                let _ = (name, custom);
+                Err(Error::NOT_SUPPORTED)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        unsafe { ffi::gcry_md_reset(self.as_raw()) }
+    }
+
+    #[inline]
+    pub fn update(&mut self, bytes: &[u8]) {
+        unsafe {
+            ffi::gcry_md_write(self.as_raw(), bytes.as_ptr().cast(), bytes.len());
+        }
+    }
+
+    #[inline]
+    pub fn finish(&mut self) {
+        unsafe {
+            ffi::gcry_md_final(self.as_raw());
+        }
+    }
+
+    #[inline]
+    pub fn get_only_digest(&mut self) -> Option<&[u8]> {
+        let algo = unsafe { ffi::gcry_md_get_algo(self.as_raw()) };
+        if algo != 0 {
+            unsafe { self.get_digest(Algorithm::from_raw(algo)) }
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn get_digest(&mut self, algo: Algorithm) -> Option<&[u8]> {
+        let len = algo.digest_len();
+        if len == 0 {
+            return None;
+        }
+
+        unsafe {
+            ffi::gcry_md_read(self.as_raw(), algo.raw())
+                .as_ref()
+                .map(|x| slice::from_raw_parts(x, len))
+        }
+    }
+
+    #[inline]
+    pub fn read_digest(&mut self, algo: Algorithm) -> Result<Digest> {
+        if !self.is_enabled(algo) {
+            return Err(Error::DIGEST_ALGO);
+        }
+        let bytes = self.get_digest(algo).ok_or(Error::DIGEST_ALGO)?.to_vec();
+        Ok(Digest { algo, bytes })
+    }
+
+    #[inline]
+    pub fn extract(&mut self, out: &mut [u8]) -> Result<()> {
+        unsafe {
+            return_err!(ffi::gcry_md_extract(
+                self.as_raw(),
+                0,
+                out.as_mut_ptr().cast(),
+                out.len()
+            ));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn into_xof_reader(self) -> XofReader {
+        XofReader(self)
+    }
+}
+
+impl Write for MessageDigest {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct XofReader(MessageDigest);
+
+impl XofReader {
+    #[inline]
+    pub fn get_ref(&self) -> &MessageDigest {
+        &self.0
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut MessageDigest {
+        &mut self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> MessageDigest {
+        self.0
+    }
+}
+
+impl Read for XofReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.extract(buf)?;
+        Ok(buf.len())
+    }
+}
+
+#[inline]
+pub fn hash(algo: Algorithm, src: &[u8], dst: &mut [u8]) {
+    assert!(algo.is_available());
+    assert!(dst.len() >= algo.digest_len());
+    let _ = crate::init_default();
+    unsafe {
+        ffi::gcry_md_hash_buffer(
+            algo.raw(),
+            dst.as_mut_ptr().cast(),
+            src.as_ptr().cast(),
+            src.len().into(),
+        );
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Digest {
+    algo: Algorithm,
+    bytes: Vec<u8>,
+}
+
+impl Digest {
+    #[inline]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algo
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl PartialEq for Digest {
+    #[inline]
+    fn eq(&self, other: &Digest) -> bool {
+        (self.algo == other.algo) && ct_eq(&self.bytes, &other.bytes)
+    }
+}
+
+impl Eq for Digest {}
+
+impl PartialEq<[u8]> for Digest {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        ct_eq(&self.bytes, other)
+    }
+}
+
+impl_hex_fmt!(Digest);
+
+pub fn hash_vectored(
+    algo: Algorithm, flags: Flags, bufs: &[IoSlice<'_>], key: Option<&[u8]>,
+) -> Result<Digest> {
+    let _ = crate::init_default();
+    let len = algo.digest_len();
+    if !algo.is_available() || (len == 0) {
+        return Err(Error::DIGEST_ALGO);
+    }
+    // With the HMAC flag set libgcrypt takes the key from the first buffer.
+    let mut flags = flags;
+    if key.is_some() {
+        flags |= Flags::HMAC;
+    } else if flags.contains(Flags::HMAC) {
+        return Err(Error::INV_ARG);
+    }
+    let iov: Vec<_> = key
+        .into_iter()
+        .chain(bufs.iter().map(|b| &b[..]))
+        .map(|b| ffi::gcry_buffer_t {
+            size: 0,
+            off: 0,
+            len: b.len(),
+            data: b.as_ptr() as *mut _,
+        })
+        .collect();
+    let count = c_int::try_from(iov.len()).map_err(|_| Error::INV_ARG)?;
+    let mut bytes = vec![0; len];
+    unsafe {
+        return_err!(ffi::gcry_md_hash_buffers(
+            algo.raw(),
+            flags.bits(),
+            bytes.as_mut_ptr().cast(),
+            iov.as_ptr(),
+            count
+        ));
+    }
+    Ok(Digest { algo, bytes })
+}
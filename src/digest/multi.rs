@@ -0,0 +1,213 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use super::{Algorithm, Digest, MessageDigest};
+use crate::{Error, Result};
+
+#[derive(Debug)]
+pub struct MultiDigest {
+    md: MessageDigest,
+    algos: Vec<Algorithm>,
+}
+
+impl MultiDigest {
+    pub fn new(algos: impl IntoIterator<Item = Algorithm>) -> Result<MultiDigest> {
+        let mut md: Option<MessageDigest> = None;
+        let mut list = Vec::new();
+        for algo in algos {
+            if list.contains(&algo) {
+                continue;
+            }
+            if algo.digest_len() == 0 {
+                return Err(Error::DIGEST_ALGO);
+            }
+            match md {
+                Some(ref mut md) => md.enable(algo)?,
+                None => md = Some(MessageDigest::new(algo)?),
+            }
+            list.push(algo);
+        }
+        Ok(MultiDigest {
+            md: md.ok_or(Error::DIGEST_ALGO)?,
+            algos: list,
+        })
+    }
+
+    #[inline]
+    pub fn algorithms(&self) -> &[Algorithm] {
+        &self.algos
+    }
+
+    #[inline]
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.md.update(bytes);
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.md.reset();
+    }
+
+    pub fn finish(mut self) -> Result<Digests> {
+        let md = &mut self.md;
+        self.algos
+            .iter()
+            .map(|&algo| md.read_digest(algo))
+            .collect::<Result<_>>()
+            .map(Digests)
+    }
+}
+
+impl Write for MultiDigest {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Digests(Vec<Digest>);
+
+impl Digests {
+    #[inline]
+    pub fn new() -> Digests {
+        Digests::default()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, algo: Algorithm) -> Option<&Digest> {
+        self.0.iter().find(|d| d.algo == algo)
+    }
+
+    #[inline]
+    pub fn contains(&self, algo: Algorithm) -> bool {
+        self.get(algo).is_some()
+    }
+
+    pub fn insert(&mut self, digest: Digest) -> Option<Digest> {
+        match self.0.iter_mut().find(|d| d.algo == digest.algo) {
+            Some(d) => Some(std::mem::replace(d, digest)),
+            None => {
+                self.0.push(digest);
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, Digest> {
+        self.0.iter()
+    }
+
+    /// Returns `true` if both sets share at least one algorithm and every shared algorithm has
+    /// the same value.
+    pub fn matches(&self, other: &Digests) -> bool {
+        let mut common = 0;
+        let mut equal = true;
+        for d in &self.0 {
+            if let Some(o) = other.get(d.algo) {
+                common += 1;
+                equal &= d == o;
+            }
+        }
+        (common > 0) && equal
+    }
+}
+
+impl PartialEq for Digests {
+    fn eq(&self, other: &Digests) -> bool {
+        (self.len() == other.len()) && self.0.iter().all(|d| other.get(d.algo) == Some(d))
+    }
+}
+
+impl Eq for Digests {}
+
+impl<'a> IntoIterator for &'a Digests {
+    type Item = &'a Digest;
+    type IntoIter = std::slice::Iter<'a, Digest>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Digests {
+    type Item = Digest;
+    type IntoIter = std::vec::IntoIter<Digest>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl fmt::Display for Digests {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, d) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            let name = d.algo.name().map_err(|_| fmt::Error)?;
+            write!(f, "{}:{:x}", name, d)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Digests {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Digests> {
+        fn nibble(c: u8) -> Result<u8> {
+            match c {
+                b'0'..=b'9' => Ok(c - b'0'),
+                b'a'..=b'f' => Ok(c - b'a' + 10),
+                b'A'..=b'F' => Ok(c - b'A' + 10),
+                _ => Err(Error::INV_DATA),
+            }
+        }
+
+        let mut result = Digests::new();
+        for item in s.split_whitespace() {
+            let mut parts = item.splitn(2, ':');
+            let name = parts.next().unwrap_or_default();
+            let hex = parts.next().ok_or(Error::INV_DATA)?.as_bytes();
+            let algo = Some(name)
+                .filter(|n| !n.contains('\0'))
+                .and_then(Algorithm::from_name)
+                .filter(|a| a.digest_len() > 0)
+                .ok_or(Error::DIGEST_ALGO)?;
+            if hex.len() != (2 * algo.digest_len()) {
+                return Err(Error::INV_DATA);
+            }
+            let bytes = hex
+                .chunks(2)
+                .map(|c| Ok((nibble(c[0])? << 4) | nibble(c[1])?))
+                .collect::<Result<_>>()?;
+            if result.insert(Digest { algo, bytes }).is_some() {
+                return Err(Error::INV_DATA);
+            }
+        }
+        Ok(result)
+    }
+}
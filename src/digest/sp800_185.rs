@@ -0,0 +1,266 @@
+use std::io::{self, Write};
+
+use super::{Algorithm, MessageDigest, XofReader};
+use crate::{utils::ct_eq, Error, Result};
+
+fn bits(len: usize) -> Result<u64> {
+    (len as u64).checked_mul(8).ok_or(Error::INV_LENGTH)
+}
+
+fn left_encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = bytes.iter().take(7).take_while(|&&b| b == 0).count();
+    let mut result = vec![(bytes.len() - skip) as u8];
+    result.extend_from_slice(&bytes[skip..]);
+    result
+}
+
+fn right_encode(x: u64) -> Vec<u8> {
+    let mut result = left_encode(x);
+    result.rotate_left(1);
+    result
+}
+
+fn encode_string(md: &mut MessageDigest, s: &[u8]) -> Result<usize> {
+    let prefix = left_encode(bits(s.len())?);
+    md.update(&prefix);
+    md.update(s);
+    Ok(prefix.len() + s.len())
+}
+
+fn open(algo: Algorithm, name: &[u8], custom: &[u8]) -> Result<MessageDigest> {
+    let mut md = MessageDigest::new(algo)?;
+    md.customize(name, custom)?;
+    Ok(md)
+}
+
+fn finish(md: &MessageDigest, out: &mut [u8]) -> Result<()> {
+    let mut md = md.try_clone()?;
+    md.update(&right_encode(bits(out.len())?));
+    md.extract(out)
+}
+
+fn finish_xof(md: &MessageDigest) -> Result<XofReader> {
+    let mut md = md.try_clone()?;
+    md.update(&right_encode(0));
+    Ok(md.into_xof_reader())
+}
+
+macro_rules! impl_kmac {
+    ($($Name:ident: $Algo:ident, $rate:expr, $min:expr;)+) => {$(
+        #[derive(Debug)]
+        pub struct $Name {
+            init: MessageDigest,
+            md: MessageDigest,
+        }
+
+        impl $Name {
+            /// The shortest tag accepted by `verify`, matching the security strength.
+            pub const MIN_TAG_LEN: usize = $min;
+
+            pub fn new(key: &[u8], custom: &[u8]) -> Result<Self> {
+                let mut init = open(Algorithm::$Algo, b"KMAC", custom)?;
+                let prefix = left_encode($rate);
+                init.update(&prefix);
+                let len = prefix.len() + encode_string(&mut init, key)?;
+                let rem = len % $rate;
+                if rem > 0 {
+                    init.update(&[0; $rate][rem..]);
+                }
+                Ok($Name {
+                    md: init.try_clone()?,
+                    init,
+                })
+            }
+
+            #[inline]
+            pub fn reset(&mut self) -> Result<()> {
+                self.md = self.init.try_clone()?;
+                Ok(())
+            }
+
+            #[inline]
+            pub fn update(&mut self, bytes: &[u8]) -> Result<()> {
+                self.md.update(bytes);
+                Ok(())
+            }
+
+            #[inline]
+            pub fn get_mac(&mut self, buf: &mut [u8]) -> Result<usize> {
+                finish(&self.md, buf)?;
+                Ok(buf.len())
+            }
+
+            pub fn verify(&mut self, buf: &[u8]) -> Result<()> {
+                // The tag length is an input to KMAC, so a short tag is a complete, weaker MAC
+                // rather than a truncation.
+                if buf.len() < Self::MIN_TAG_LEN {
+                    return Err(Error::INV_LENGTH);
+                }
+                let mut expected = vec![0; buf.len()];
+                finish(&self.md, &mut expected)?;
+                if !ct_eq(&expected, buf) {
+                    return Err(Error::CHECKSUM);
+                }
+                Ok(())
+            }
+
+            #[inline]
+            pub fn xof_reader(&self) -> Result<XofReader> {
+                finish_xof(&self.md)
+            }
+        }
+
+        impl Write for $Name {
+            #[inline]
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.update(buf)?;
+                Ok(buf.len())
+            }
+
+            #[inline]
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+    )+};
+}
+
+impl_kmac! {
+    Kmac128: CShake128, 168, 16;
+    Kmac256: CShake256, 136, 32;
+}
+
+macro_rules! impl_tuple_hash {
+    ($($Name:ident: $Algo:ident;)+) => {$(
+        #[derive(Debug)]
+        pub struct $Name(MessageDigest);
+
+        impl $Name {
+            #[inline]
+            pub fn new(custom: &[u8]) -> Result<Self> {
+                open(Algorithm::$Algo, b"TupleHash", custom).map($Name)
+            }
+
+            #[inline]
+            pub fn update(&mut self, item: &[u8]) -> Result<()> {
+                encode_string(&mut self.0, item).map(|_| ())
+            }
+
+            #[inline]
+            pub fn finish(&self, out: &mut [u8]) -> Result<()> {
+                finish(&self.0, out)
+            }
+
+            #[inline]
+            pub fn xof_reader(&self) -> Result<XofReader> {
+                finish_xof(&self.0)
+            }
+        }
+    )+};
+}
+
+impl_tuple_hash! {
+    TupleHash128: CShake128;
+    TupleHash256: CShake256;
+}
+
+macro_rules! impl_parallel_hash {
+    ($($Name:ident: $Algo:ident, $Leaf:ident, $leaf_len:expr;)+) => {$(
+        #[derive(Debug)]
+        pub struct $Name {
+            md: MessageDigest,
+            leaf: MessageDigest,
+            block_size: usize,
+            blocks: u64,
+            partial: Vec<u8>,
+        }
+
+        impl $Name {
+            pub fn new(block_size: usize, custom: &[u8]) -> Result<Self> {
+                if block_size == 0 {
+                    return Err(Error::INV_ARG);
+                }
+                let mut md = open(Algorithm::$Algo, b"ParallelHash", custom)?;
+                md.update(&left_encode(block_size as u64));
+                Ok($Name {
+                    md,
+                    leaf: MessageDigest::new(Algorithm::$Leaf)?,
+                    block_size,
+                    blocks: 0,
+                    partial: Vec::with_capacity(block_size),
+                })
+            }
+
+            fn compress(md: &mut MessageDigest, leaf: &mut MessageDigest, block: &[u8]) -> Result<()> {
+                let mut output = [0u8; $leaf_len];
+                leaf.reset();
+                leaf.update(block);
+                leaf.extract(&mut output)?;
+                md.update(&output);
+                Ok(())
+            }
+
+            pub fn update(&mut self, mut bytes: &[u8]) -> Result<()> {
+                while !bytes.is_empty() {
+                    if self.partial.is_empty() && (bytes.len() >= self.block_size) {
+                        let (block, rest) = bytes.split_at(self.block_size);
+                        Self::compress(&mut self.md, &mut self.leaf, block)?;
+                        self.blocks += 1;
+                        bytes = rest;
+                        continue;
+                    }
+                    let n = (self.block_size - self.partial.len()).min(bytes.len());
+                    self.partial.extend_from_slice(&bytes[..n]);
+                    bytes = &bytes[n..];
+                    if self.partial.len() == self.block_size {
+                        Self::compress(&mut self.md, &mut self.leaf, &self.partial)?;
+                        self.blocks += 1;
+                        self.partial.clear();
+                    }
+                }
+                Ok(())
+            }
+
+            fn tail(&self) -> Result<MessageDigest> {
+                let mut md = self.md.try_clone()?;
+                let mut blocks = self.blocks;
+                if !self.partial.is_empty() {
+                    let mut leaf = MessageDigest::new(Algorithm::$Leaf)?;
+                    Self::compress(&mut md, &mut leaf, &self.partial)?;
+                    blocks += 1;
+                }
+                md.update(&right_encode(blocks));
+                Ok(md)
+            }
+
+            #[inline]
+            pub fn finish(&self, out: &mut [u8]) -> Result<()> {
+                finish(&self.tail()?, out)
+            }
+
+            #[inline]
+            pub fn xof_reader(&self) -> Result<XofReader> {
+                finish_xof(&self.tail()?)
+            }
+        }
+
+        impl Write for $Name {
+            #[inline]
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.update(buf)?;
+                Ok(buf.len())
+            }
+
+            #[inline]
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+    )+};
+}
+
+impl_parallel_hash! {
+    ParallelHash128: CShake128, Shake128, 32;
+    ParallelHash256: CShake256, Shake256, 64;
+}
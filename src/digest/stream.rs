@@ -0,0 +1,206 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use super::{Algorithm, Digest, MessageDigest};
+use crate::{Error, Result};
+
+pub fn hash_reader(algo: Algorithm, mut reader: impl Read) -> Result<Digest> {
+    let mut md = MessageDigest::new(algo)?;
+    io::copy(&mut reader, &mut md)?;
+    md.read_digest(algo)
+}
+
+pub fn hash_file(algo: Algorithm, path: impl AsRef<Path>) -> Result<Digest> {
+    hash_reader(algo, File::open(path)?)
+}
+
+fn open(algos: &[Algorithm]) -> Result<MessageDigest> {
+    let (&first, rest) = algos.split_first().ok_or(Error::DIGEST_ALGO)?;
+    let mut md = MessageDigest::new(first)?;
+    for &algo in rest {
+        md.enable(algo)?;
+    }
+    Ok(md)
+}
+
+struct Progress {
+    count: u64,
+    callback: Option<Box<dyn FnMut(u64)>>,
+}
+
+impl Progress {
+    fn new() -> Progress {
+        Progress {
+            count: 0,
+            callback: None,
+        }
+    }
+
+    fn update(&mut self, n: usize) {
+        self.count += n as u64;
+        if let Some(ref mut callback) = self.callback {
+            callback(self.count);
+        }
+    }
+}
+
+pub struct HashingReader<R> {
+    inner: R,
+    digest: MessageDigest,
+    progress: Progress,
+}
+
+impl<R: fmt::Debug> fmt::Debug for HashingReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashingReader")
+            .field("inner", &self.inner)
+            .field("digest", &self.digest)
+            .field("count", &self.progress.count)
+            .finish()
+    }
+}
+
+impl<R: Read> HashingReader<R> {
+    #[inline]
+    pub fn new(inner: R, digest: MessageDigest) -> Self {
+        HashingReader {
+            inner,
+            digest,
+            progress: Progress::new(),
+        }
+    }
+
+    #[inline]
+    pub fn with_algorithms(inner: R, algos: &[Algorithm]) -> Result<Self> {
+        Ok(HashingReader::new(inner, open(algos)?))
+    }
+
+    #[inline]
+    pub fn set_progress(&mut self, callback: impl FnMut(u64) + 'static) {
+        self.progress.callback = Some(Box::new(callback));
+    }
+
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.progress.count
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn digest(&self) -> &MessageDigest {
+        &self.digest
+    }
+
+    #[inline]
+    pub fn digest_mut(&mut self) -> &mut MessageDigest {
+        &mut self.digest
+    }
+
+    #[inline]
+    pub fn into_parts(self) -> (R, MessageDigest) {
+        (self.inner, self.digest)
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.digest.update(&buf[..n]);
+        self.progress.update(n);
+        Ok(n)
+    }
+}
+
+pub struct HashingWriter<W> {
+    inner: W,
+    digest: MessageDigest,
+    progress: Progress,
+}
+
+impl<W: fmt::Debug> fmt::Debug for HashingWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashingWriter")
+            .field("inner", &self.inner)
+            .field("digest", &self.digest)
+            .field("count", &self.progress.count)
+            .finish()
+    }
+}
+
+impl<W: Write> HashingWriter<W> {
+    #[inline]
+    pub fn new(inner: W, digest: MessageDigest) -> Self {
+        HashingWriter {
+            inner,
+            digest,
+            progress: Progress::new(),
+        }
+    }
+
+    #[inline]
+    pub fn with_algorithms(inner: W, algos: &[Algorithm]) -> Result<Self> {
+        Ok(HashingWriter::new(inner, open(algos)?))
+    }
+
+    #[inline]
+    pub fn set_progress(&mut self, callback: impl FnMut(u64) + 'static) {
+        self.progress.callback = Some(Box::new(callback));
+    }
+
+    #[inline]
+    pub fn bytes_written(&self) -> u64 {
+        self.progress.count
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn digest(&self) -> &MessageDigest {
+        &self.digest
+    }
+
+    #[inline]
+    pub fn digest_mut(&mut self) -> &mut MessageDigest {
+        &mut self.digest
+    }
+
+    #[inline]
+    pub fn into_parts(self) -> (W, MessageDigest) {
+        (self.inner, self.digest)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.digest.update(&buf[..n]);
+        self.progress.update(n);
+        Ok(n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,651 @@
+use std::{
+    cmp, fmt,
+    io::{self, Read, Write},
+};
+
+use libc::c_int;
+
+use crate::{
+    cipher::{aead, Algorithm as CipherAlgorithm, Mode},
+    digest::Algorithm as DigestAlgorithm,
+    kdf::{self, Algorithm as KdfAlgorithm},
+    rand::{self, Level},
+    utils::wipe,
+    Error, Result,
+};
+
+const MAGIC: &[u8; 8] = b"GCRYENVL";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 32;
+const MIN_SALT_LEN: usize = 16;
+const MAX_SALT_LEN: usize = 64;
+const TAG_LEN: usize = 16;
+const MAX_CHUNK_SIZE: u32 = 1 << 24;
+
+fn nonce_len(mode: Mode) -> Result<usize> {
+    match mode {
+        Mode::Gcm | Mode::Ccm | Mode::Ocb | Mode::Poly1305 | Mode::GcmSiv => Ok(12),
+        Mode::Eax => Ok(16),
+        _ => Err(Error::INV_CIPHER_MODE),
+    }
+}
+
+fn max_chunk_size(mode: Mode) -> u32 {
+    match mode {
+        // A 12-byte nonce leaves a 3-byte length field.
+        Mode::Ccm => MAX_CHUNK_SIZE - 1,
+        _ => MAX_CHUNK_SIZE,
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Limits {
+    scrypt_n: u32,
+    scrypt_p: u32,
+    iterations: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            scrypt_n: 1 << 20,
+            scrypt_p: 16,
+            iterations: 10_000_000,
+        }
+    }
+}
+
+impl Limits {
+    const NONE: Limits = Limits {
+        scrypt_n: u32::MAX,
+        scrypt_p: u32::MAX,
+        iterations: u32::MAX,
+    };
+
+    #[inline]
+    pub fn scrypt(&mut self, max_n: u32, max_p: u32) -> &mut Self {
+        self.scrypt_n = max_n;
+        self.scrypt_p = max_p;
+        self
+    }
+
+    #[inline]
+    pub fn pbkdf2(&mut self, max_iterations: u32) -> &mut Self {
+        self.iterations = max_iterations;
+        self
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Params {
+    kdf: KdfAlgorithm,
+    subalgo: u32,
+    iterations: u32,
+    cipher: CipherAlgorithm,
+    mode: Mode,
+    chunk_size: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            kdf: KdfAlgorithm::Scrypt,
+            subalgo: 1 << 15,
+            iterations: 1,
+            cipher: CipherAlgorithm::Aes256,
+            mode: Mode::Gcm,
+            chunk_size: 1 << 16,
+        }
+    }
+}
+
+impl Params {
+    #[inline]
+    pub fn scrypt(&mut self, n: u32, p: u32) -> &mut Self {
+        self.kdf = KdfAlgorithm::Scrypt;
+        self.subalgo = n;
+        self.iterations = p;
+        self
+    }
+
+    #[inline]
+    pub fn pbkdf2(&mut self, digest: DigestAlgorithm, iterations: u32) -> &mut Self {
+        self.kdf = KdfAlgorithm::Pbkdf2;
+        self.subalgo = digest.raw() as u32;
+        self.iterations = iterations;
+        self
+    }
+
+    #[inline]
+    pub fn cipher(&mut self, algo: CipherAlgorithm, mode: Mode) -> &mut Self {
+        self.cipher = algo;
+        self.mode = mode;
+        self
+    }
+
+    #[inline]
+    pub fn chunk_size(&mut self, size: u32) -> &mut Self {
+        self.chunk_size = size;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    kdf: KdfAlgorithm,
+    subalgo: u32,
+    iterations: u32,
+    salt: Vec<u8>,
+    cipher: CipherAlgorithm,
+    mode: Mode,
+    nonce: Vec<u8>,
+    chunk_size: u32,
+}
+
+impl Header {
+    fn new(params: &Params) -> Result<Header> {
+        let mut salt = vec![0; SALT_LEN];
+        rand::randomize(Level::Strong, &mut salt);
+        let mut nonce = vec![0; nonce_len(params.mode)?];
+        rand::make_nonce(&mut nonce);
+        let header = Header {
+            kdf: params.kdf,
+            subalgo: params.subalgo,
+            iterations: params.iterations,
+            salt,
+            cipher: params.cipher,
+            mode: params.mode,
+            nonce,
+            chunk_size: params.chunk_size,
+        };
+        header.validate(&Limits::NONE)?;
+        Ok(header)
+    }
+
+    fn validate(&self, limits: &Limits) -> Result<()> {
+        match self.kdf {
+            KdfAlgorithm::Scrypt => {
+                if (self.subalgo < 2) || !self.subalgo.is_power_of_two() || (self.iterations == 0) {
+                    return Err(Error::INV_VALUE);
+                }
+                if (self.subalgo > limits.scrypt_n) || (self.iterations > limits.scrypt_p) {
+                    return Err(Error::TOO_LARGE);
+                }
+            }
+            KdfAlgorithm::Pbkdf2 => {
+                let digest = self.digest();
+                if !digest.is_available() || digest.is_xof() {
+                    return Err(Error::DIGEST_ALGO);
+                }
+                if self.iterations == 0 {
+                    return Err(Error::INV_VALUE);
+                }
+                if self.iterations > limits.iterations {
+                    return Err(Error::TOO_LARGE);
+                }
+            }
+            _ => return Err(Error::UNSUPPORTED_ALGORITHM),
+        }
+        if !(MIN_SALT_LEN..=MAX_SALT_LEN).contains(&self.salt.len()) {
+            return Err(Error::INV_LENGTH);
+        }
+        if self.cipher.key_len() == 0 {
+            return Err(Error::CIPHER_ALGO);
+        }
+        let valid = match self.mode {
+            Mode::Poly1305 => self.cipher == CipherAlgorithm::Chacha20,
+            _ => self.cipher.block_len() == 16,
+        };
+        if !valid {
+            return Err(Error::INV_CIPHER_MODE);
+        }
+        if self.nonce.len() != nonce_len(self.mode)? {
+            return Err(Error::INV_LENGTH);
+        }
+        if (self.chunk_size == 0) || (self.chunk_size > max_chunk_size(self.mode)) {
+            return Err(Error::INV_ARG);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn read_from(reader: impl Read) -> Result<Header> {
+        Header::read_with_limits(reader, &Limits::default())
+    }
+
+    pub fn read_with_limits(mut reader: impl Read, limits: &Limits) -> Result<Header> {
+        fn read(reader: &mut impl Read, buf: &mut [u8]) -> Result<()> {
+            reader.read_exact(buf).map_err(|e| {
+                if e.kind() == io::ErrorKind::UnexpectedEof {
+                    Error::TRUNCATED
+                } else {
+                    Error::from(e)
+                }
+            })
+        }
+        fn read_u32(reader: &mut impl Read) -> Result<u32> {
+            let mut buf = [0; 4];
+            read(reader, &mut buf)?;
+            Ok(u32::from_be_bytes(buf))
+        }
+        fn read_vec(reader: &mut impl Read) -> Result<Vec<u8>> {
+            let mut len = [0; 1];
+            read(reader, &mut len)?;
+            let mut buf = vec![0; usize::from(len[0])];
+            read(reader, &mut buf)?;
+            Ok(buf)
+        }
+
+        let mut magic = [0; 9];
+        read(&mut reader, &mut magic)?;
+        if magic[..8] != MAGIC[..] {
+            return Err(Error::INV_DATA);
+        } else if magic[8] != VERSION {
+            return Err(Error::UNKNOWN_VERSION);
+        }
+        let header = unsafe {
+            Header {
+                kdf: KdfAlgorithm::from_raw(read_u32(&mut reader)? as c_int),
+                subalgo: read_u32(&mut reader)?,
+                iterations: read_u32(&mut reader)?,
+                salt: read_vec(&mut reader)?,
+                cipher: CipherAlgorithm::from_raw(read_u32(&mut reader)? as c_int),
+                mode: Mode::from_raw(read_u32(&mut reader)? as c_int),
+                nonce: read_vec(&mut reader)?,
+                chunk_size: read_u32(&mut reader)?,
+            }
+        };
+        header.validate(limits)?;
+        Ok(header)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.push(VERSION);
+        buf.extend_from_slice(&(self.kdf.raw() as u32).to_be_bytes());
+        buf.extend_from_slice(&self.subalgo.to_be_bytes());
+        buf.extend_from_slice(&self.iterations.to_be_bytes());
+        buf.push(self.salt.len() as u8);
+        buf.extend_from_slice(&self.salt);
+        buf.extend_from_slice(&(self.cipher.raw() as u32).to_be_bytes());
+        buf.extend_from_slice(&(self.mode.raw() as u32).to_be_bytes());
+        buf.push(self.nonce.len() as u8);
+        buf.extend_from_slice(&self.nonce);
+        buf.extend_from_slice(&self.chunk_size.to_be_bytes());
+        buf
+    }
+
+    #[inline]
+    pub fn kdf(&self) -> KdfAlgorithm {
+        self.kdf
+    }
+
+    #[inline]
+    pub fn kdf_subalgo(&self) -> u32 {
+        self.subalgo
+    }
+
+    #[inline]
+    pub fn kdf_iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    #[inline]
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    #[inline]
+    pub fn cipher(&self) -> CipherAlgorithm {
+        self.cipher
+    }
+
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn nonce(&self) -> &[u8] {
+        &self.nonce
+    }
+
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size as usize
+    }
+
+    fn digest(&self) -> DigestAlgorithm {
+        unsafe { DigestAlgorithm::from_raw(self.subalgo as c_int) }
+    }
+
+    fn derive_key(&self, password: &[u8]) -> Result<Key> {
+        let mut key = Key(vec![0; self.cipher.key_len()]);
+        match self.kdf {
+            KdfAlgorithm::Scrypt => kdf::scrypt_derive(
+                self.subalgo,
+                self.iterations,
+                password,
+                &self.salt,
+                &mut key.0,
+            )?,
+            _ => kdf::pbkdf2_derive(
+                self.digest(),
+                self.iterations,
+                password,
+                &self.salt,
+                &mut key.0,
+            )?,
+        }
+        Ok(key)
+    }
+}
+
+struct Key(Vec<u8>);
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+struct State {
+    header: Header,
+    aad: Vec<u8>,
+    key: Key,
+    counter: u64,
+}
+
+impl State {
+    fn new(header: Header, password: &[u8]) -> Result<State> {
+        let key = header.derive_key(password)?;
+        let aad = header.to_bytes();
+        Ok(State {
+            header,
+            aad,
+            key,
+            counter: 0,
+        })
+    }
+
+    // Each chunk gets its own nonce by mixing in the chunk counter, and its AAD binds the header,
+    // the position and whether it is the final chunk so that chunks cannot be reordered, dropped
+    // or truncated undetected.
+    fn next(&mut self, last: bool) -> Result<(Vec<u8>, Vec<u8>)> {
+        let counter = self.counter.to_be_bytes();
+        self.counter = self.counter.checked_add(1).ok_or(Error::TOO_LARGE)?;
+        let mut nonce = self.header.nonce.clone();
+        let offset = nonce.len() - counter.len();
+        for (n, c) in nonce[offset..].iter_mut().zip(counter.iter()) {
+            *n ^= c;
+        }
+        let mut aad = self.aad.clone();
+        aad.extend_from_slice(&counter);
+        aad.push(last as u8);
+        Ok((nonce, aad))
+    }
+
+    fn seal(&mut self, chunk: &mut Vec<u8>, last: bool) -> Result<()> {
+        let (nonce, aad) = self.next(last)?;
+        let len = chunk.len();
+        chunk.resize(len + TAG_LEN, 0);
+        let (data, tag) = chunk.split_at_mut(len);
+        aead::encrypt(
+            self.header.cipher,
+            self.header.mode,
+            &self.key.0,
+            &nonce,
+            &aad,
+            data,
+            tag,
+        )
+    }
+
+    fn open(&mut self, chunk: &mut Vec<u8>, last: bool) -> Result<()> {
+        let (nonce, aad) = self.next(last)?;
+        let len = chunk.len().checked_sub(TAG_LEN).ok_or(Error::TRUNCATED)?;
+        let (data, tag) = chunk.split_at_mut(len);
+        aead::decrypt(
+            self.header.cipher,
+            self.header.mode,
+            &self.key.0,
+            &nonce,
+            &aad,
+            data,
+            tag,
+        )?;
+        chunk.truncate(len);
+        Ok(())
+    }
+}
+
+pub struct Sealer<W: Write> {
+    inner: W,
+    state: State,
+    buf: Vec<u8>,
+    out: Vec<u8>,
+    failed: bool,
+}
+
+impl<W: Write + fmt::Debug> fmt::Debug for Sealer<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sealer")
+            .field("inner", &self.inner)
+            .field("header", &self.state.header)
+            .finish()
+    }
+}
+
+impl<W: Write> Sealer<W> {
+    pub fn new(inner: W, params: &Params, password: &[u8]) -> Result<Self> {
+        let state = State::new(Header::new(params)?, password)?;
+        let out = state.aad.clone();
+        Ok(Sealer {
+            inner,
+            state,
+            buf: Vec::new(),
+            out,
+            failed: false,
+        })
+    }
+
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.state.header
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    fn dump(&mut self) -> io::Result<()> {
+        while !self.out.is_empty() {
+            match self.inner.write(&self.out) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.out.drain(..n);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    // The counter and the chunk buffer cannot be restored once sealing a chunk fails, so the
+    // sealer refuses any further use.
+    fn seal(&mut self, last: bool) -> Result<()> {
+        if self.failed {
+            return Err(Error::INV_STATE);
+        }
+        if let Err(e) = self.state.seal(&mut self.buf, last) {
+            self.failed = true;
+            wipe(&mut self.buf);
+            self.buf.clear();
+            return Err(e);
+        }
+        self.out.append(&mut self.buf);
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.dump()?;
+        self.seal(true)?;
+        self.dump()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Sealer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.failed {
+            return Err(Error::INV_STATE.into());
+        }
+        self.dump()?;
+        let chunk_size = self.state.header.chunk_size();
+        // A full chunk is only sealed once more data arrives, since the final chunk is marked.
+        if (self.buf.len() == chunk_size) && !buf.is_empty() {
+            self.seal(false)?;
+        }
+        let len = cmp::min(buf.len(), chunk_size - self.buf.len());
+        self.buf.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.dump()?;
+        self.inner.flush()
+    }
+}
+
+pub struct Opener<R: Read> {
+    inner: R,
+    state: State,
+    pending: Vec<u8>,
+    out: Vec<u8>,
+    pos: usize,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: Read + fmt::Debug> fmt::Debug for Opener<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Opener")
+            .field("inner", &self.inner)
+            .field("header", &self.state.header)
+            .finish()
+    }
+}
+
+impl<R: Read> Opener<R> {
+    #[inline]
+    pub fn new(inner: R, password: &[u8]) -> Result<Self> {
+        Opener::with_limits(inner, password, &Limits::default())
+    }
+
+    pub fn with_limits(mut inner: R, password: &[u8], limits: &Limits) -> Result<Self> {
+        let header = Header::read_with_limits(&mut inner, limits)?;
+        Ok(Opener {
+            inner,
+            state: State::new(header, password)?,
+            pending: Vec::new(),
+            out: Vec::new(),
+            pos: 0,
+            eof: false,
+            done: false,
+        })
+    }
+
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.state.header
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let sealed_len = self.state.header.chunk_size() + TAG_LEN;
+        // Read one byte past a full chunk to learn whether it is the final one.
+        while !self.eof && (self.pending.len() <= sealed_len) {
+            let start = self.pending.len();
+            self.pending.resize(sealed_len + 1, 0);
+            match self.inner.read(&mut self.pending[start..]) {
+                Ok(n) => {
+                    self.pending.truncate(start + n);
+                    self.eof = n == 0;
+                }
+                Err(e) => {
+                    self.pending.truncate(start);
+                    if e.kind() != io::ErrorKind::Interrupted {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        let last = self.pending.len() <= sealed_len;
+        let len = cmp::min(self.pending.len(), sealed_len);
+        let mut chunk = self.pending[..len].to_vec();
+        self.state.open(&mut chunk, last)?;
+        self.pending.drain(..len);
+        self.out = chunk;
+        self.pos = 0;
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Opener<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.out.len() {
+                let n = cmp::min(buf.len(), self.out.len() - self.pos);
+                buf[..n].copy_from_slice(&self.out[self.pos..(self.pos + n)]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if self.done || buf.is_empty() {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+    }
+}
+
+pub fn seal_with_password(params: &Params, password: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut sealer = Sealer::new(Vec::new(), params, password)?;
+    sealer.write_all(plaintext)?;
+    Ok(sealer.finish()?)
+}
+
+pub fn open_with_password(password: &[u8], envelope: &[u8]) -> Result<Vec<u8>> {
+    let mut opener = Opener::new(envelope, password)?;
+    let mut output = Vec::new();
+    if let Err(e) = opener.read_to_end(&mut output) {
+        wipe(&mut output);
+        return Err(e.into());
+    }
+    Ok(output)
+}
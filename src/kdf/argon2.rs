@@ -0,0 +1,289 @@
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+use ffi;
+use libc::{c_int, c_uint, c_ulong, c_void};
+
+use super::Algorithm;
+use crate::{error::return_err, require_gcrypt_ver, Error, Result};
+
+ffi_enum_wrapper! {
+    pub enum Argon2Variant: c_int {
+        Argon2d  = ffi::GCRY_KDF_ARGON2D,
+        Argon2i  = ffi::GCRY_KDF_ARGON2I,
+        Argon2id = ffi::GCRY_KDF_ARGON2ID,
+    }
+}
+
+#[derive(Default)]
+struct Pending {
+    count: Mutex<usize>,
+    done: Condvar,
+}
+
+impl Pending {
+    fn add(&self) {
+        *self.count.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+    }
+
+    fn remove(&self) {
+        let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        *count -= 1;
+        if *count == 0 {
+            self.done.notify_all();
+        }
+    }
+
+    fn wait(&self) {
+        let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        while *count > 0 {
+            count = self.done.wait(count).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+/// A unit of work computing one segment of one lane.
+///
+/// A job that is dropped without being run is run by its destructor, so an executor can never
+/// leave libgcrypt waiting on work that will not happen.
+pub struct Job {
+    func: Option<unsafe extern "C" fn(*mut c_void)>,
+    data: *mut c_void,
+    pending: Arc<Pending>,
+}
+
+unsafe impl Send for Job {}
+
+impl Job {
+    #[inline]
+    pub fn run(mut self) {
+        self.run_once();
+    }
+
+    fn run_once(&mut self) {
+        if let Some(func) = self.func.take() {
+            unsafe {
+                func(self.data);
+            }
+            self.pending.remove();
+        }
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        self.run_once();
+    }
+}
+
+impl fmt::Debug for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Job").field("data", &self.data).finish()
+    }
+}
+
+pub trait Executor {
+    fn execute(&self, job: Job);
+}
+
+impl<F: Fn(Job)> Executor for F {
+    #[inline]
+    fn execute(&self, job: Job) {
+        self(job)
+    }
+}
+
+/// Runs every job on a newly spawned thread.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ThreadExecutor;
+
+impl Executor for ThreadExecutor {
+    fn execute(&self, job: Job) {
+        // If the thread cannot be spawned the closure, and with it the job, is dropped and so
+        // runs on the current thread instead.
+        let _ = thread::Builder::new().spawn(move || job.run());
+    }
+}
+
+struct Jobs<'a> {
+    executor: &'a (dyn Executor + 'a),
+    pending: Arc<Pending>,
+}
+
+unsafe extern "C" fn dispatch_job(
+    ctx: *mut c_void, func: ffi::gcry_kdf_job_fn_t, data: *mut c_void,
+) -> c_int {
+    let jobs = &*(ctx as *const Jobs<'_>);
+    if func.is_none() {
+        return -1;
+    }
+    jobs.pending.add();
+    let job = Job {
+        func,
+        data,
+        pending: jobs.pending.clone(),
+    };
+    match panic::catch_unwind(AssertUnwindSafe(|| jobs.executor.execute(job))) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn wait_all_jobs(ctx: *mut c_void) -> c_int {
+    let jobs = &*(ctx as *const Jobs<'_>);
+    jobs.pending.wait();
+    0
+}
+
+/// Argon2 as specified in RFC 9106. The memory cost is given in KiB.
+#[derive(Copy, Clone)]
+pub struct Argon2<'a> {
+    variant: Argon2Variant,
+    t_cost: u32,
+    m_cost: u32,
+    parallelism: u32,
+    secret: &'a [u8],
+    ad: &'a [u8],
+    executor: Option<&'a (dyn Executor + 'a)>,
+}
+
+impl fmt::Debug for Argon2<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Argon2")
+            .field("variant", &self.variant)
+            .field("t_cost", &self.t_cost)
+            .field("m_cost", &self.m_cost)
+            .field("parallelism", &self.parallelism)
+            .field("threaded", &self.executor.is_some())
+            .finish()
+    }
+}
+
+impl<'a> Argon2<'a> {
+    #[inline]
+    pub fn new(variant: Argon2Variant) -> Argon2<'a> {
+        Argon2 {
+            variant,
+            t_cost: 3,
+            m_cost: 64 * 1024,
+            parallelism: 4,
+            secret: &[],
+            ad: &[],
+            executor: None,
+        }
+    }
+
+    #[inline]
+    pub fn variant(&self) -> Argon2Variant {
+        self.variant
+    }
+
+    #[inline]
+    pub fn t_cost(&mut self, t_cost: u32) -> &mut Self {
+        self.t_cost = t_cost;
+        self
+    }
+
+    #[inline]
+    pub fn m_cost(&mut self, m_cost: u32) -> &mut Self {
+        self.m_cost = m_cost;
+        self
+    }
+
+    #[inline]
+    pub fn parallelism(&mut self, parallelism: u32) -> &mut Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    #[inline]
+    pub fn secret(&mut self, secret: &'a [u8]) -> &mut Self {
+        self.secret = secret;
+        self
+    }
+
+    #[inline]
+    pub fn associated_data(&mut self, ad: &'a [u8]) -> &mut Self {
+        self.ad = ad;
+        self
+    }
+
+    #[inline]
+    pub fn executor(&mut self, executor: &'a (dyn Executor + 'a)) -> &mut Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    pub fn derive(&self, password: &[u8], salt: &[u8], key: &mut [u8]) -> Result<()> {
+        if key.len() < 4 {
+            return Err(Error::INV_LENGTH);
+        }
+        if (self.t_cost == 0)
+            || !(1..=0xff_ffff).contains(&self.parallelism)
+            || (self.m_cost < 8 * self.parallelism)
+        {
+            return Err(Error::INV_VALUE);
+        }
+        require_gcrypt_ver! {
+            (1, 10) => {
+                let _ = crate::init_default();
+                let param = [
+                    key.len() as c_ulong,
+                    self.t_cost.into(),
+                    self.m_cost.into(),
+                    self.parallelism.into(),
+                ];
+                unsafe {
+                    let mut hd = ptr::null_mut();
+                    return_err!(ffi::gcry_kdf_open(
+                        &mut hd,
+                        Algorithm::Argon2.raw(),
+                        self.variant.raw(),
+                        param.as_ptr(),
+                        param.len() as c_uint,
+                        password.as_ptr().cast(),
+                        password.len(),
+                        salt.as_ptr().cast(),
+                        salt.len(),
+                        self.secret.as_ptr().cast(),
+                        self.secret.len(),
+                        self.ad.as_ptr().cast(),
+                        self.ad.len(),
+                    ));
+                    let mut result = match self.executor {
+                        Some(executor) => {
+                            let jobs = Jobs {
+                                executor,
+                                pending: Arc::default(),
+                            };
+                            let ops = ffi::gcry_kdf_thread_ops_t {
+                                jobs_context: &jobs as *const Jobs<'_> as *mut c_void,
+                                dispatch_job: Some(dispatch_job),
+                                wait_all_jobs: Some(wait_all_jobs),
+                            };
+                            let result = ffi::gcry_kdf_compute(hd, &ops);
+                            // Jobs may still be in flight if the computation was abandoned.
+                            jobs.pending.wait();
+                            result
+                        }
+                        None => ffi::gcry_kdf_compute(hd, ptr::null()),
+                    };
+                    if result == 0 {
+                        result = ffi::gcry_kdf_final(hd, key.len(), key.as_mut_ptr().cast());
+                    }
+                    ffi::gcry_kdf_close(hd);
+                    return_err!(result);
+                }
+                Ok(())
+            } else {
+                let _ = (password, salt);
+                Err(Error::NOT_SUPPORTED)
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::ptr;
+
+use ffi;
+use libc::c_int;
+
+use crate::{
+    digest::Algorithm as DigestAlgorithm,
+    error::return_err,
+    mac::{Algorithm as MacAlgorithm, Mac},
+    require_gcrypt_ver,
+    utils::wipe,
+    Error, Result,
+};
+
+mod argon2;
+
+pub use self::argon2::{Argon2, Argon2Variant, Executor, Job, ThreadExecutor};
+
+ffi_enum_wrapper! {
+    pub enum Algorithm: c_int {
+        SimpleS2K = ffi::GCRY_KDF_SIMPLE_S2K,
+        SaltedS2K = ffi::GCRY_KDF_SALTED_S2K,
+        IteratedSaltedS2K = ffi::GCRY_KDF_ITERSALTED_S2K,
+        Pbkdf1 = ffi::GCRY_KDF_PBKDF1,
+        Pbkdf2 = ffi::GCRY_KDF_PBKDF2,
+        Scrypt = ffi::GCRY_KDF_SCRYPT,
+        Argon2 = ffi::GCRY_KDF_ARGON2,
+        Hkdf = ffi::GCRY_KDF_HKDF,
+    }
+}
+
+#[inline]
+pub fn derive(
+    algo: Algorithm, subalgo: i32, iter: u32, secret: &[u8], salt: Option<&[u8]>, key: &mut [u8],
+) -> Result<()> {
+    let _ = crate::init_default();
+    unsafe {
+        let salt = salt.map_or((ptr::null(), 0), |s| (s.as_ptr(), s.len()));
+        return_err!(ffi::gcry_kdf_derive(
+            secret.as_ptr().cast(),
+            secret.len(),
+            algo.raw(),
+            subalgo as c_int,
+            salt.0.cast(),
+            salt.1,
+            iter.into(),
+            key.len(),
+            key.as_mut_ptr().cast(),
+        ));
+    }
+    Ok(())
+}
+
+#[inline]
+pub fn s2k_derive(
+    digest: DigestAlgorithm, iter: u32, secret: &[u8], salt: Option<&[u8]>, key: &mut [u8],
+) -> Result<()> {
+    let variant = match (iter, salt.is_some()) {
+        (0, true) => Algorithm::SaltedS2K,
+        (_, true) => Algorithm::IteratedSaltedS2K,
+        _ => Algorithm::SimpleS2K,
+    };
+    derive(variant, digest.raw(), iter, secret, salt, key)
+}
+
+#[inline]
+pub fn pbkdf1_derive(
+    digest: DigestAlgorithm, iter: u32, secret: &[u8], salt: &[u8], key: &mut [u8],
+) -> Result<()> {
+    derive(
+        Algorithm::Pbkdf1,
+        digest.raw(),
+        iter,
+        secret,
+        Some(salt),
+        key,
+    )
+}
+
+#[inline]
+pub fn pbkdf2_derive(
+    digest: DigestAlgorithm, iter: u32, secret: &[u8], salt: &[u8], key: &mut [u8],
+) -> Result<()> {
+    derive(
+        Algorithm::Pbkdf2,
+        digest.raw(),
+        iter,
+        secret,
+        Some(salt),
+        key,
+    )
+}
+
+#[inline]
+pub fn scrypt_derive(n: u32, p: u32, secret: &[u8], salt: &[u8], key: &mut [u8]) -> Result<()> {
+    derive(Algorithm::Scrypt, n as i32, p, secret, Some(salt), key)
+}
+
+fn hmac_algorithm(digest: DigestAlgorithm) -> Result<MacAlgorithm> {
+    Ok(match digest {
+        DigestAlgorithm::Md5 => MacAlgorithm::HmacMd5,
+        DigestAlgorithm::Sha1 => MacAlgorithm::HmacSha1,
+        DigestAlgorithm::Rmd160 => MacAlgorithm::HmacRmd160,
+        DigestAlgorithm::Sha224 => MacAlgorithm::HmacSha224,
+        DigestAlgorithm::Sha256 => MacAlgorithm::HmacSha256,
+        DigestAlgorithm::Sha384 => MacAlgorithm::HmacSha384,
+        DigestAlgorithm::Sha512 => MacAlgorithm::HmacSha512,
+        DigestAlgorithm::Sha512_224 => MacAlgorithm::HmacSha512_224,
+        DigestAlgorithm::Sha512_256 => MacAlgorithm::HmacSha512_256,
+        DigestAlgorithm::Sha3_224 => MacAlgorithm::HmacSha3_224,
+        DigestAlgorithm::Sha3_256 => MacAlgorithm::HmacSha3_256,
+        DigestAlgorithm::Sha3_384 => MacAlgorithm::HmacSha3_384,
+        DigestAlgorithm::Sha3_512 => MacAlgorithm::HmacSha3_512,
+        DigestAlgorithm::Whirlpool => MacAlgorithm::HmacWhirlpool,
+        DigestAlgorithm::Stribog256 => MacAlgorithm::HmacStribog256,
+        DigestAlgorithm::Stribog512 => MacAlgorithm::HmacStribog512,
+        DigestAlgorithm::Blake2B512 => MacAlgorithm::HmacBlake2b512,
+        DigestAlgorithm::Blake2B384 => MacAlgorithm::HmacBlake2b384,
+        DigestAlgorithm::Blake2B256 => MacAlgorithm::HmacBlake2b256,
+        DigestAlgorithm::Blake2S256 => MacAlgorithm::HmacBlake2s256,
+        DigestAlgorithm::Blake2S224 => MacAlgorithm::HmacBlake2s224,
+        DigestAlgorithm::Sm3 => MacAlgorithm::HmacSm3,
+        _ => return Err(Error::DIGEST_ALGO),
+    })
+}
+
+// An absent or empty salt is equivalent to HashLen zero bytes once HMAC pads the key, so the
+// explicit form is always used.
+fn hkdf_salt(salt: Option<&[u8]>, len: usize) -> Vec<u8> {
+    match salt {
+        Some(salt) if !salt.is_empty() => salt.to_vec(),
+        _ => vec![0; len],
+    }
+}
+
+pub fn hkdf_extract(
+    digest: DigestAlgorithm, secret: &[u8], salt: Option<&[u8]>, prk: &mut [u8],
+) -> Result<()> {
+    let algo = hmac_algorithm(digest)?;
+    if prk.len() != algo.mac_len() {
+        return Err(Error::INV_LENGTH);
+    }
+    let mut mac = Mac::new(algo)?;
+    mac.set_key(hkdf_salt(salt, prk.len()))?;
+    mac.update(secret)?;
+    mac.get_mac(prk)?;
+    Ok(())
+}
+
+pub fn hkdf_expand(digest: DigestAlgorithm, prk: &[u8], info: &[u8], key: &mut [u8]) -> Result<()> {
+    let algo = hmac_algorithm(digest)?;
+    let len = algo.mac_len();
+    if (prk.len() < len) || (key.len() > 255 * len) {
+        return Err(Error::INV_LENGTH);
+    }
+    let mut mac = Mac::new(algo)?;
+    mac.set_key(prk)?;
+    let mut block = [0u8; 64];
+    let mut prev = 0;
+    for (i, chunk) in key.chunks_mut(len).enumerate() {
+        mac.update(&block[..prev])?;
+        mac.update(info)?;
+        mac.update(&[(i + 1) as u8])?;
+        prev = mac.get_mac(&mut block)?;
+        chunk.copy_from_slice(&block[..chunk.len()]);
+        mac.reset()?;
+    }
+    wipe(&mut block);
+    Ok(())
+}
+
+pub fn hkdf(
+    digest: DigestAlgorithm, secret: &[u8], salt: Option<&[u8]>, info: &[u8], key: &mut [u8],
+) -> Result<()> {
+    let len = hmac_algorithm(digest)?.mac_len();
+    if key.len() > 255 * len {
+        return Err(Error::INV_LENGTH);
+    }
+    require_gcrypt_ver! {
+        (1, 11) => {
+            // libgcrypt refuses empty input keying material, which RFC 5869 permits.
+            if !secret.is_empty() {
+                let mac = hmac_algorithm(digest)?;
+                let _ = crate::init_default();
+                let salt = hkdf_salt(salt, len);
+                let param = [key.len() as libc::c_ulong];
+                // libgcrypt ignores the salt argument for HKDF, taking the salt from the key
+                // argument and the info from the additional data.
+                unsafe {
+                    let mut hd = ptr::null_mut();
+                    return_err!(ffi::gcry_kdf_open(
+                        &mut hd,
+                        Algorithm::Hkdf.raw(),
+                        mac.raw(),
+                        param.as_ptr(),
+                        param.len() as libc::c_uint,
+                        secret.as_ptr().cast(),
+                        secret.len(),
+                        ptr::null(),
+                        0,
+                        salt.as_ptr().cast(),
+                        salt.len(),
+                        info.as_ptr().cast(),
+                        info.len(),
+                    ));
+                    let mut result = ffi::gcry_kdf_compute(hd, ptr::null());
+                    if result == 0 {
+                        result = ffi::gcry_kdf_final(hd, key.len(), key.as_mut_ptr().cast());
+                    }
+                    ffi::gcry_kdf_close(hd);
+                    return_err!(result);
+                }
+                return Ok(());
+            }
+        }
+    }
+    let mut prk = [0u8; 64];
+    let prk = &mut prk[..len];
+    let result =
+        hkdf_extract(digest, secret, salt, prk).and_then(|_| hkdf_expand(digest, prk, info, key));
+    wipe(prk);
+    result
+}
@@ -45,6 +45,7 @@ mod utils;
 pub mod buffer;
 pub mod cipher;
 pub mod digest;
+pub mod envelope;
 pub mod kdf;
 pub mod mac;
 pub mod mpi;
@@ -1,6 +1,7 @@
 use std::{
+    cmp,
     ffi::CStr,
-    io::{self, Write},
+    io::{self, IoSlice, Write},
     ptr, result,
     str::Utf8Error,
 };
@@ -10,7 +11,18 @@ use cstr_argument::CStrArgument;
 use ffi;
 use libc::c_int;
 
-use crate::{error::return_err, NonNull, Result};
+use crate::{
+    error::return_err,
+    utils::{base64_encode, ct_eq},
+    Error, NonNull, Result,
+};
+
+pub mod otp;
+#[cfg(feature = "digest")]
+mod typed;
+
+#[cfg(feature = "digest")]
+pub use self::typed::*;
 
 ffi_enum_wrapper! {
     #[allow(non_camel_case_types)]
@@ -60,12 +72,15 @@ ffi_enum_wrapper! {
         CmacIdea          = ffi::GCRY_MAC_CMAC_IDEA,
         CmacGost28147     = ffi::GCRY_MAC_CMAC_GOST28147,
         CmacSm4           = ffi::GCRY_MAC_CMAC_SM4,
+        CmacAria          = ffi::GCRY_MAC_CMAC_ARIA,
 
         GmacAes           = ffi::GCRY_MAC_GMAC_AES,
         GmacCamellia      = ffi::GCRY_MAC_GMAC_CAMELLIA,
         GmacTwofish       = ffi::GCRY_MAC_GMAC_TWOFISH,
         GmacSerpent       = ffi::GCRY_MAC_GMAC_SERPENT,
         GmacSeed          = ffi::GCRY_MAC_GMAC_SEED,
+        GmacSm4           = ffi::GCRY_MAC_GMAC_SM4,
+        GmacAria          = ffi::GCRY_MAC_GMAC_ARIA,
 
         Poly1305          = ffi::GCRY_MAC_POLY1305,
         Poly1305Aes       = ffi::GCRY_MAC_POLY1305_AES,
@@ -73,6 +88,8 @@ ffi_enum_wrapper! {
         Poly1305Twofish   = ffi::GCRY_MAC_POLY1305_TWOFISH,
         Poly1305Serpent   = ffi::GCRY_MAC_POLY1305_SERPENT,
         Poly1305Seed      = ffi::GCRY_MAC_POLY1305_SEED,
+        Poly1305Sm4       = ffi::GCRY_MAC_POLY1305_SM4,
+        Poly1305Aria      = ffi::GCRY_MAC_POLY1305_ARIA,
     }
 }
 
@@ -246,3 +263,135 @@ impl Write for Mac {
         Ok(())
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct Tag {
+    algo: Algorithm,
+    bytes: Vec<u8>,
+}
+
+impl Tag {
+    #[inline]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algo
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.bytes.truncate(len);
+    }
+
+    #[inline]
+    pub fn to_base64(&self) -> String {
+        base64_encode(&self.bytes)
+    }
+}
+
+impl AsRef<[u8]> for Tag {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl PartialEq for Tag {
+    #[inline]
+    fn eq(&self, other: &Tag) -> bool {
+        (self.algo == other.algo) && ct_eq(&self.bytes, &other.bytes)
+    }
+}
+
+impl Eq for Tag {}
+
+impl PartialEq<[u8]> for Tag {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        ct_eq(&self.bytes, other)
+    }
+}
+
+impl_hex_fmt!(Tag);
+
+fn open(algo: Algorithm, key: &[u8], iv: Option<&[u8]>) -> Result<Mac> {
+    let mut mac = Mac::new(algo)?;
+    mac.set_key(key)?;
+    if let Some(iv) = iv {
+        mac.set_iv(iv)?;
+    }
+    Ok(mac)
+}
+
+#[inline]
+pub fn compute(algo: Algorithm, key: &[u8], data: &[u8]) -> Result<Tag> {
+    compute_vectored(algo, key, None, &[IoSlice::new(data)])
+}
+
+pub fn compute_vectored(
+    algo: Algorithm, key: &[u8], iv: Option<&[u8]>, bufs: &[IoSlice<'_>],
+) -> Result<Tag> {
+    let mut mac = open(algo, key, iv)?;
+    for buf in bufs {
+        mac.update(buf)?;
+    }
+    let mut bytes = vec![0; algo.mac_len()];
+    let len = mac.get_mac(&mut bytes)?;
+    bytes.truncate(len);
+    Ok(Tag { algo, bytes })
+}
+
+#[inline]
+pub fn verify(algo: Algorithm, key: &[u8], data: &[u8], tag: &[u8]) -> Result<()> {
+    verify_vectored(algo, key, None, &[IoSlice::new(data)], tag)
+}
+
+pub fn verify_vectored(
+    algo: Algorithm, key: &[u8], iv: Option<&[u8]>, bufs: &[IoSlice<'_>], tag: &[u8],
+) -> Result<()> {
+    // libgcrypt accepts any prefix of the tag, so the length has to be checked here.
+    if tag.len() != algo.mac_len() {
+        return Err(Error::INV_LENGTH);
+    }
+    verify_tag(algo, key, iv, bufs, tag)
+}
+
+/// The shortest tag `verify_truncated` accepts: 80 bits, as recommended by RFC 2104.
+pub const MIN_TRUNCATED_LEN: usize = 10;
+
+pub fn verify_truncated(
+    algo: Algorithm, key: &[u8], data: &[u8], tag: &[u8], min_len: usize,
+) -> Result<()> {
+    if (tag.len() < cmp::max(min_len, MIN_TRUNCATED_LEN)) || (tag.len() > algo.mac_len()) {
+        return Err(Error::INV_LENGTH);
+    }
+    verify_tag(algo, key, None, &[IoSlice::new(data)], tag)
+}
+
+fn verify_tag(
+    algo: Algorithm, key: &[u8], iv: Option<&[u8]>, bufs: &[IoSlice<'_>], tag: &[u8],
+) -> Result<()> {
+    let mut mac = open(algo, key, iv)?;
+    for buf in bufs {
+        mac.update(buf)?;
+    }
+    mac.verify(tag)
+}
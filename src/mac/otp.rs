@@ -0,0 +1,421 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{Algorithm, Mac};
+use crate::{
+    utils::{ct_eq, wipe},
+    Error, Result,
+};
+
+/// The most codes besides the expected one that a single verification will try, following the
+/// look-ahead parameter of RFC 4226, section 7.4.
+pub const MAX_WINDOW: u64 = 10;
+
+/// The shortest secret allowed by RFC 4226, section 4.
+pub const MIN_SECRET_LEN: usize = 16;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(8 * (data.len() / 5 + 1));
+    for chunk in data.chunks(5) {
+        let mut block = [0u8; 8];
+        block[3..(3 + chunk.len())].copy_from_slice(chunk);
+        let n = u64::from_be_bytes(block);
+        for i in 0..(chunk.len() * 8).div_ceil(5) {
+            result.push(char::from(
+                BASE32_ALPHABET[((n >> (35 - 5 * i)) & 0x1f) as usize],
+            ));
+        }
+    }
+    result
+}
+
+fn base32_decode(s: &str) -> Result<Vec<u8>> {
+    let mut result = Vec::with_capacity(s.len() * 5 / 8);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in s.trim_end_matches('=').bytes() {
+        let v = match c.to_ascii_uppercase() {
+            c @ b'A'..=b'Z' => c - b'A',
+            c @ b'2'..=b'7' => c - b'2' + 26,
+            _ => return Err(Error::INV_VALUE),
+        };
+        acc = (acc << 5) | u32::from(v);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(result)
+}
+
+fn percent_encode(s: &str, keep: &[u8]) -> String {
+    let mut result = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) || keep.contains(&b) {
+            result.push(char::from(b));
+        } else {
+            result.push_str(&format!("%{:02X}", b));
+        }
+    }
+    result
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let mut result = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next(), bytes.next()];
+            let hex = match hex {
+                [Some(h), Some(l)] => [h, l],
+                _ => return Err(Error::INV_URI),
+            };
+            let hex = std::str::from_utf8(&hex).map_err(|_| Error::INV_URI)?;
+            result.push(u8::from_str_radix(hex, 16).map_err(|_| Error::INV_URI)?);
+        } else {
+            result.push(b);
+        }
+    }
+    String::from_utf8(result).map_err(|_| Error::INV_URI)
+}
+
+fn algorithm_name(algo: Algorithm) -> &'static str {
+    match algo {
+        Algorithm::HmacSha256 => "SHA256",
+        Algorithm::HmacSha512 => "SHA512",
+        _ => "SHA1",
+    }
+}
+
+fn now() -> Result<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|_| Error::INV_VALUE)
+}
+
+#[derive(Clone)]
+pub struct Hotp {
+    algo: Algorithm,
+    secret: Vec<u8>,
+    digits: u32,
+}
+
+impl Drop for Hotp {
+    fn drop(&mut self) {
+        wipe(&mut self.secret);
+    }
+}
+
+impl fmt::Debug for Hotp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hotp")
+            .field("algo", &self.algo)
+            .field("digits", &self.digits)
+            .finish()
+    }
+}
+
+impl Hotp {
+    pub fn new(algo: Algorithm, secret: &[u8]) -> Result<Hotp> {
+        match algo {
+            Algorithm::HmacSha1 | Algorithm::HmacSha256 | Algorithm::HmacSha512 => (),
+            _ => return Err(Error::MAC_ALGO),
+        }
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(Error::INV_KEYLEN);
+        }
+        Ok(Hotp {
+            algo,
+            secret: secret.to_vec(),
+            digits: 6,
+        })
+    }
+
+    #[inline]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algo
+    }
+
+    #[inline]
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    #[inline]
+    pub fn digit_count(&self) -> u32 {
+        self.digits
+    }
+
+    #[inline]
+    pub fn digits(&mut self, digits: u32) -> Result<&mut Self> {
+        if !(6..=9).contains(&digits) {
+            return Err(Error::INV_VALUE);
+        }
+        self.digits = digits;
+        Ok(self)
+    }
+
+    pub fn generate(&self, counter: u64) -> Result<String> {
+        let mut mac = Mac::new(self.algo)?;
+        mac.set_key(&self.secret)?;
+        mac.update(&counter.to_be_bytes())?;
+        let mut hash = [0u8; 64];
+        let len = mac.get_mac(&mut hash)?;
+        // Dynamic truncation from RFC 4226, section 5.3.
+        let offset = usize::from(hash[len - 1] & 0xf);
+        let mut code = [0u8; 4];
+        code.copy_from_slice(&hash[offset..(offset + 4)]);
+        let code = u32::from_be_bytes(code) & 0x7fff_ffff;
+        wipe(&mut hash);
+        Ok(format!(
+            "{:0width$}",
+            code % 10u32.pow(self.digits),
+            width = self.digits as usize
+        ))
+    }
+
+    #[inline]
+    pub fn verify(&self, code: &str, counter: u64, look_ahead: u64) -> Result<u64> {
+        if look_ahead > MAX_WINDOW {
+            return Err(Error::INV_VALUE);
+        }
+        self.verify_range(code, counter, look_ahead)
+    }
+
+    fn verify_range(&self, code: &str, counter: u64, look_ahead: u64) -> Result<u64> {
+        let end = counter.saturating_add(look_ahead);
+        let mut found = None;
+        for c in counter..=end {
+            if ct_eq(self.generate(c)?.as_bytes(), code.as_bytes()) && found.is_none() {
+                found = Some(c);
+            }
+        }
+        found.ok_or(Error::CHECKSUM)
+    }
+
+    pub fn to_uri(&self, label: &str, issuer: Option<&str>, counter: u64) -> String {
+        let mut uri = uri_prefix("hotp", self, label, issuer);
+        uri.push_str(&format!("&counter={}", counter));
+        uri
+    }
+}
+
+fn uri_prefix(kind: &str, hotp: &Hotp, label: &str, issuer: Option<&str>) -> String {
+    let mut uri = format!(
+        "otpauth://{}/{}?secret={}",
+        kind,
+        percent_encode(label, b":"),
+        base32_encode(&hotp.secret)
+    );
+    if let Some(issuer) = issuer {
+        uri.push_str(&format!("&issuer={}", percent_encode(issuer, b"")));
+    }
+    uri.push_str(&format!(
+        "&algorithm={}&digits={}",
+        algorithm_name(hotp.algo),
+        hotp.digits
+    ));
+    uri
+}
+
+#[derive(Debug, Clone)]
+pub struct Totp {
+    hotp: Hotp,
+    step: u64,
+    t0: u64,
+    behind: u64,
+    ahead: u64,
+}
+
+impl Totp {
+    #[inline]
+    pub fn new(algo: Algorithm, secret: &[u8]) -> Result<Totp> {
+        Ok(Totp {
+            hotp: Hotp::new(algo, secret)?,
+            step: 30,
+            t0: 0,
+            behind: 1,
+            ahead: 1,
+        })
+    }
+
+    #[inline]
+    pub fn hotp(&self) -> &Hotp {
+        &self.hotp
+    }
+
+    #[inline]
+    pub fn time_step(&self) -> u64 {
+        self.step
+    }
+
+    #[inline]
+    pub fn digits(&mut self, digits: u32) -> Result<&mut Self> {
+        self.hotp.digits(digits)?;
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn step(&mut self, step: u64) -> Result<&mut Self> {
+        if step == 0 {
+            return Err(Error::INV_VALUE);
+        }
+        self.step = step;
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn t0(&mut self, t0: u64) -> &mut Self {
+        self.t0 = t0;
+        self
+    }
+
+    #[inline]
+    pub fn window(&mut self, behind: u64, ahead: u64) -> Result<&mut Self> {
+        if behind.saturating_add(ahead) > MAX_WINDOW {
+            return Err(Error::INV_VALUE);
+        }
+        self.behind = behind;
+        self.ahead = ahead;
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn counter_at(&self, time: u64) -> Result<u64> {
+        time.checked_sub(self.t0)
+            .map(|t| t / self.step)
+            .ok_or(Error::INV_VALUE)
+    }
+
+    #[inline]
+    pub fn generate_at(&self, time: u64) -> Result<String> {
+        self.hotp.generate(self.counter_at(time)?)
+    }
+
+    #[inline]
+    pub fn generate(&self) -> Result<String> {
+        self.generate_at(now()?)
+    }
+
+    pub fn verify_at(&self, code: &str, time: u64) -> Result<u64> {
+        let counter = self.counter_at(time)?;
+        let start = counter.saturating_sub(self.behind);
+        let ahead = (counter - start).saturating_add(self.ahead);
+        self.hotp.verify_range(code, start, ahead)
+    }
+
+    #[inline]
+    pub fn verify(&self, code: &str) -> Result<u64> {
+        self.verify_at(code, now()?)
+    }
+
+    pub fn to_uri(&self, label: &str, issuer: Option<&str>) -> String {
+        let mut uri = uri_prefix("totp", &self.hotp, label, issuer);
+        uri.push_str(&format!("&period={}", self.step));
+        uri
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum OtpAuth {
+    Hotp {
+        hotp: Hotp,
+        counter: u64,
+        label: String,
+        issuer: Option<String>,
+    },
+    Totp {
+        totp: Totp,
+        label: String,
+        issuer: Option<String>,
+    },
+}
+
+impl OtpAuth {
+    pub fn parse(uri: &str) -> Result<OtpAuth> {
+        let rest = uri.strip_prefix("otpauth://").ok_or(Error::INV_URI)?;
+        let (kind, rest) = rest.split_at(rest.find('/').ok_or(Error::INV_URI)?);
+        if (kind != "hotp") && (kind != "totp") {
+            return Err(Error::INV_URI);
+        }
+        let rest = &rest[1..];
+        let (label, query) = match rest.find('?') {
+            Some(i) => (&rest[..i], &rest[(i + 1)..]),
+            None => (rest, ""),
+        };
+        let label = percent_decode(label)?;
+
+        let mut secret = None;
+        let mut issuer = None;
+        let mut algo = Algorithm::HmacSha1;
+        let mut digits = 6;
+        let mut counter = None;
+        let mut period = 30;
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = match param.find('=') {
+                Some(i) => (&param[..i], percent_decode(&param[(i + 1)..])?),
+                None => return Err(Error::INV_URI),
+            };
+            match key {
+                "secret" => secret = Some(base32_decode(&value)?),
+                "issuer" => issuer = Some(value),
+                "algorithm" => {
+                    algo = match &value.to_ascii_uppercase()[..] {
+                        "SHA1" => Algorithm::HmacSha1,
+                        "SHA256" => Algorithm::HmacSha256,
+                        "SHA512" => Algorithm::HmacSha512,
+                        _ => return Err(Error::MAC_ALGO),
+                    }
+                }
+                "digits" => digits = value.parse().map_err(|_| Error::INV_VALUE)?,
+                "counter" => counter = Some(value.parse().map_err(|_| Error::INV_VALUE)?),
+                "period" => period = value.parse().map_err(|_| Error::INV_VALUE)?,
+                _ => (),
+            }
+        }
+        let secret = secret.ok_or(Error::INV_URI)?;
+
+        if kind == "hotp" {
+            let counter = counter.ok_or(Error::INV_URI)?;
+            let mut hotp = Hotp::new(algo, &secret)?;
+            hotp.digits(digits)?;
+            Ok(OtpAuth::Hotp {
+                hotp,
+                counter,
+                label,
+                issuer,
+            })
+        } else {
+            let mut totp = Totp::new(algo, &secret)?;
+            totp.digits(digits)?.step(period)?;
+            Ok(OtpAuth::Totp {
+                totp,
+                label,
+                issuer,
+            })
+        }
+    }
+
+    pub fn to_uri(&self) -> String {
+        match self {
+            OtpAuth::Hotp {
+                hotp,
+                counter,
+                label,
+                issuer,
+            } => hotp.to_uri(label, issuer.as_deref(), *counter),
+            OtpAuth::Totp {
+                totp,
+                label,
+                issuer,
+            } => totp.to_uri(label, issuer.as_deref()),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use ::digest::{
+    consts::{U104, U12, U128, U136, U144, U16, U20, U24, U28, U32, U48, U64, U72},
+    crypto_common::{Iv, IvSizeUser, KeyIvInit, KeySizeUser},
+    typenum::Unsigned,
+    FixedOutput, FixedOutputReset, InvalidLength, Key, KeyInit, MacError, MacMarker, Output,
+    OutputSizeUser, Reset, Update,
+};
+
+use super::{open, Algorithm, Mac};
+
+macro_rules! impl_mac_common {
+    ($Name:ident, $OutputSize:ty) => {
+        #[derive(Debug)]
+        pub struct $Name(Mac);
+
+        impl $Name {
+            // libgcrypt accepts truncated tags, so the length is checked here to keep the
+            // semantics of `digest::Mac::verify_slice`.
+            #[inline]
+            pub fn verify_slice(mut self, tag: &[u8]) -> ::std::result::Result<(), MacError> {
+                if tag.len() != <$OutputSize>::USIZE {
+                    return Err(MacError);
+                }
+                self.0.verify(tag).map_err(|_| MacError)
+            }
+        }
+
+        impl MacMarker for $Name {}
+
+        impl OutputSizeUser for $Name {
+            type OutputSize = $OutputSize;
+        }
+
+        impl Update for $Name {
+            #[inline]
+            fn update(&mut self, data: &[u8]) {
+                self.0.update(data).expect("unable to update mac");
+            }
+        }
+
+        impl FixedOutput for $Name {
+            #[inline]
+            fn finalize_into(mut self, out: &mut Output<Self>) {
+                self.0.get_mac(out).expect("unable to read mac");
+            }
+        }
+    };
+}
+
+macro_rules! impl_keyed_mac {
+    ($($Name:ident: $Algo:ident, $KeySize:ty, $OutputSize:ty, $any_key_len:expr;)+) => {$(
+        impl_mac_common!($Name, $OutputSize);
+
+        impl KeySizeUser for $Name {
+            type KeySize = $KeySize;
+        }
+
+        impl KeyInit for $Name {
+            #[inline]
+            fn new(key: &Key<Self>) -> Self {
+                $Name(open(Algorithm::$Algo, key, None).expect("unable to initialize mac"))
+            }
+
+            #[inline]
+            fn new_from_slice(key: &[u8]) -> ::std::result::Result<Self, InvalidLength> {
+                if !$any_key_len && (key.len() != <$KeySize>::USIZE) {
+                    return Err(InvalidLength);
+                }
+                open(Algorithm::$Algo, key, None)
+                    .map($Name)
+                    .map_err(|_| InvalidLength)
+            }
+        }
+
+        impl Reset for $Name {
+            #[inline]
+            fn reset(&mut self) {
+                self.0.reset().expect("unable to reset mac");
+            }
+        }
+
+        impl FixedOutputReset for $Name {
+            #[inline]
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                self.0.get_mac(out).expect("unable to read mac");
+                self.reset();
+            }
+        }
+    )+};
+}
+
+// HMAC keys may have any length; the nominal key size is the block size of the hash.
+impl_keyed_mac! {
+    HmacMd5:         HmacMd5,        U64,  U16, true;
+    HmacSha1:        HmacSha1,       U64,  U20, true;
+    HmacRmd160:      HmacRmd160,     U64,  U20, true;
+    HmacSha224:      HmacSha224,     U64,  U28, true;
+    HmacSha256:      HmacSha256,     U64,  U32, true;
+    HmacSha384:      HmacSha384,     U128, U48, true;
+    HmacSha512:      HmacSha512,     U128, U64, true;
+    HmacSha512_224:  HmacSha512_224, U128, U28, true;
+    HmacSha512_256:  HmacSha512_256, U128, U32, true;
+    HmacSha3_224:    HmacSha3_224,   U144, U28, true;
+    HmacSha3_256:    HmacSha3_256,   U136, U32, true;
+    HmacSha3_384:    HmacSha3_384,   U104, U48, true;
+    HmacSha3_512:    HmacSha3_512,   U72,  U64, true;
+    HmacBlake2b512:  HmacBlake2b512, U128, U64, true;
+    HmacBlake2s256:  HmacBlake2s256, U64,  U32, true;
+    HmacSm3:         HmacSm3,        U64,  U32, true;
+    CmacAes128:      CmacAes,        U16,  U16, false;
+    CmacAes192:      CmacAes,        U24,  U16, false;
+    CmacAes256:      CmacAes,        U32,  U16, false;
+    CmacCamellia128: CmacCamellia,   U16,  U16, false;
+    CmacCamellia256: CmacCamellia,   U32,  U16, false;
+    CmacSm4:         CmacSm4,        U16,  U16, false;
+}
+
+macro_rules! impl_nonce_mac {
+    ($($Name:ident: $Algo:ident, $KeySize:ty, $IvSize:ty;)+) => {$(
+        impl_mac_common!($Name, U16);
+
+        impl KeySizeUser for $Name {
+            type KeySize = $KeySize;
+        }
+
+        impl IvSizeUser for $Name {
+            type IvSize = $IvSize;
+        }
+
+        impl KeyIvInit for $Name {
+            #[inline]
+            fn new(key: &Key<Self>, iv: &Iv<Self>) -> Self {
+                $Name(open(Algorithm::$Algo, key, Some(iv)).expect("unable to initialize mac"))
+            }
+        }
+    )+};
+}
+
+// GMAC and Poly1305-AES take a nonce that must not be reused with the same key, so these types
+// do not implement `Reset`.
+impl_nonce_mac! {
+    GmacAes128:  GmacAes,     U16, U12;
+    GmacAes256:  GmacAes,     U32, U12;
+    Poly1305Aes: Poly1305Aes, U32, U16;
+}
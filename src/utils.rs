@@ -73,6 +73,35 @@ macro_rules! ffi_enum_wrapper {
     };
 }
 
+macro_rules! impl_hex_fmt {
+    ($Name:ty) => {
+        impl ::std::fmt::LowerHex for $Name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                for b in self.as_bytes() {
+                    write!(f, "{:02x}", b)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl ::std::fmt::UpperHex for $Name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                for b in self.as_bytes() {
+                    write!(f, "{:02X}", b)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl ::std::fmt::Display for $Name {
+            #[inline]
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::LowerHex::fmt(self, f)
+            }
+        }
+    };
+}
+
 pub(crate) trait Ptr {
     type Inner;
 }
@@ -84,3 +113,40 @@ impl<T> Ptr for *mut T {
 impl<T> Ptr for *const T {
     type Inner = T;
 }
+
+pub(crate) fn wipe(buf: &mut [u8]) {
+    for b in buf {
+        unsafe {
+            ::std::ptr::write_volatile(b, 0);
+        }
+    }
+}
+
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    unsafe { ::std::ptr::read_volatile(&diff) == 0 }
+}
+
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::with_capacity(4 * (data.len() / 3 + 1));
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(char::from(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize]));
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
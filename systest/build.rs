@@ -17,17 +17,68 @@ fn main() {
         "gcry_ctl_cmds" | "gcry_sexp_format" | "gcry_mpi_format" | "gcry_mpi_flag"
         | "gcry_cipher_algos" | "gcry_cipher_modes" | "gcry_cipher_flags" | "gcry_pk_algos"
         | "gcry_md_algos" | "gcry_md_flags" | "gcry_mac_algos" | "gcry_mac_flags"
-        | "gcry_kdf_algos" | "gcry_random_level" | "gcry_log_levels" => format!("enum {}", s),
+        | "gcry_kdf_algos" | "gcry_kdf_subalgo_argon2" | "gcry_random_level"
+        | "gcry_log_levels" => format!("enum {}", s),
         s if is_struct && !s.ends_with("_t") => format!("struct {}", s),
         s => s.to_string(),
     });
-    cfg.skip_struct(|s| match s {
+    // Items introduced after the detected library version are not in its header.
+    let version = env::var("DEP_GCRYPT_VERSION")
+        .ok()
+        .and_then(|v| {
+            let mut parts = v.trim().split('.').map(|x| x.parse::<u32>().ok());
+            Some((parts.next()??, parts.next()??))
+        })
+        .unwrap_or((1, 0));
+    cfg.skip_struct(move |s| match s {
         // Opaque structs
         "gcry_thread_cbs" | "gcry_context" | "gcry_sexp" | "gcry_mpi" | "gcry_mpi_point"
-        | "gcry_cipher_handle" | "gcry_md_handle" | "gcry_mac_handle" => true,
+        | "gcry_cipher_handle" | "gcry_md_handle" | "gcry_mac_handle" | "gcry_kdf_handle" => true,
+        "gcry_kdf_thread_ops" => version < (1, 10),
+        "gcry_cshake_customization" => version < (1, 11),
         _ => false,
     });
     cfg.skip_signededness(|s| s.ends_with("_t"));
 
+    cfg.skip_const(move |s| match s {
+        "GCRY_CIPHER_EXTENDED" => version < (1, 9),
+        "GCRY_CIPHER_MODE_SIV"
+        | "GCRY_CIPHER_MODE_GCM_SIV"
+        | "GCRYCTL_SET_DECRYPTION_TAG"
+        | "GCRY_KDF_ARGON2"
+        | "GCRY_KDF_ARGON2D"
+        | "GCRY_KDF_ARGON2I"
+        | "GCRY_KDF_ARGON2ID" => version < (1, 10),
+        "GCRY_CIPHER_ARIA128"
+        | "GCRY_CIPHER_ARIA192"
+        | "GCRY_CIPHER_ARIA256"
+        | "GCRY_MAC_CMAC_ARIA"
+        | "GCRY_MAC_GMAC_SM4"
+        | "GCRY_MAC_GMAC_ARIA"
+        | "GCRY_MAC_POLY1305_SM4"
+        | "GCRY_MAC_POLY1305_ARIA"
+        | "GCRY_MD_CSHAKE128"
+        | "GCRY_MD_CSHAKE256"
+        | "GCRYCTL_MD_CUSTOMIZE"
+        | "GCRY_KDF_HKDF" => version < (1, 11),
+        _ => false,
+    });
+
+    cfg.skip_type(move |s| match s {
+        "gcry_kdf_subalgo_argon2"
+        | "gcry_kdf_hd_t"
+        | "gcry_kdf_job_fn_t"
+        | "gcry_kdf_dispatch_job_fn_t"
+        | "gcry_kdf_wait_all_jobs_fn_t"
+        | "gcry_kdf_thread_ops_t" => version < (1, 10),
+        _ => false,
+    });
+    cfg.skip_fn(move |s| match s {
+        "gcry_kdf_open" | "gcry_kdf_compute" | "gcry_kdf_final" | "gcry_kdf_close" => {
+            version < (1, 10)
+        }
+        _ => false,
+    });
+
     cfg.generate("../libgcrypt-sys/lib.rs", "all.rs");
 }
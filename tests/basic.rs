@@ -303,6 +303,1061 @@ fn test_bulk_cipher_modes() {
     }
 }
 
+#[test]
+fn test_aead() {
+    use gcrypt::cipher::aead;
+
+    setup();
+
+    let key = b"0123456789abcdef.,;/[]{}-=ABCDEF";
+    let specs = [
+        (CipherAlgorithm::Aes128, CipherMode::Gcm, 12),
+        (CipherAlgorithm::Aes256, CipherMode::Gcm, 12),
+        (CipherAlgorithm::Aes128, CipherMode::Ccm, 13),
+        (CipherAlgorithm::Aes256, CipherMode::Ccm, 7),
+        (CipherAlgorithm::Aes128, CipherMode::Ocb, 12),
+        (CipherAlgorithm::Aes256, CipherMode::Eax, 16),
+        (CipherAlgorithm::Camellia128, CipherMode::Gcm, 12),
+        (CipherAlgorithm::Chacha20, CipherMode::Poly1305, 12),
+    ];
+    for &(algo, mode, nonce_len) in specs.iter() {
+        if !algo.is_available() {
+            continue;
+        }
+
+        let key = &key[..algo.key_len()];
+        let nonce = &b"foobar42FOOBAR17"[..nonce_len];
+        let msg = b"0123456789abcdef0123";
+        let mut sealed = aead::seal(algo, mode, key, nonce, b"header", msg).unwrap();
+        assert_eq!(sealed.len(), msg.len() + 16);
+        assert_eq!(
+            aead::open(algo, mode, key, nonce, b"header", &sealed).unwrap(),
+            &msg[..]
+        );
+        assert_eq!(
+            aead::open(algo, mode, key, nonce, b"footer", &sealed)
+                .unwrap_err()
+                .code(),
+            Error::CHECKSUM.code()
+        );
+        assert!(aead::open(algo, mode, key, nonce, b"header", &sealed[..15]).is_err());
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert_eq!(
+            aead::open(algo, mode, key, nonce, b"header", &sealed)
+                .unwrap_err()
+                .code(),
+            Error::CHECKSUM.code()
+        );
+    }
+
+    assert!(aead::seal(
+        CipherAlgorithm::Aes128,
+        CipherMode::Cbc,
+        &key[..16],
+        &[0; 16],
+        b"",
+        b"",
+    )
+    .is_err());
+
+    let sealed = aead::seal(
+        CipherAlgorithm::Aes128,
+        CipherMode::Gcm,
+        &[0; 16],
+        &[0; 12],
+        b"",
+        &[0; 16],
+    )
+    .unwrap();
+    assert_eq!(
+        &sealed[..],
+        &b"\x03\x88\xda\xce\x60\xb6\xa3\x92\xf3\x28\xc2\xb9\x71\xb2\xfe\x78\
+           \xab\x6e\x47\xd4\x2c\xec\x13\xbd\xf5\x3a\x67\xb2\x12\x57\xbd\xdf"[..]
+    );
+
+    let sealed = aead::seal(
+        CipherAlgorithm::Aes128,
+        CipherMode::Ocb,
+        b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f",
+        b"\xbb\xaa\x99\x88\x77\x66\x55\x44\x33\x22\x11\x00",
+        b"",
+        b"",
+    )
+    .unwrap();
+    assert_eq!(
+        &sealed[..],
+        &b"\x78\x54\x07\xbf\xff\xc8\xad\x9e\xdc\xc5\x52\x0a\xc9\x11\x1e\xe6"[..]
+    );
+
+    let sealed = aead::seal(
+        CipherAlgorithm::Aes128,
+        CipherMode::Eax,
+        b"\x23\x39\x52\xde\xe4\xd5\xed\x5f\x9b\x9c\x6d\x6f\xf8\x0f\xf4\x78",
+        b"\x62\xec\x67\xf9\xc3\xa4\xa4\x07\xfc\xb2\xa8\xc4\x90\x31\xa8\xb3",
+        b"\x6b\xfb\x91\x4f\xd0\x7e\xae\x6b",
+        b"",
+    )
+    .unwrap();
+    assert_eq!(
+        &sealed[..],
+        &b"\xe0\x37\x83\x0e\x83\x89\xf2\x7b\x02\x5a\x2d\x65\x27\xe7\x9d\x01"[..]
+    );
+}
+
+#[cfg(feature = "aead")]
+fn check_aead_traits<A: aead::Aead + aead::KeyInit>() {
+    use aead::Payload;
+
+    let key = aead::Key::<A>::from_exact_iter(0..(A::key_size() as u8)).unwrap();
+    let nonce = aead::Nonce::<A>::default();
+    let cipher = A::new(&key);
+    let msg = b"foobar42FOOBAR17foobar";
+    let aad = b"header";
+
+    let mut ct = cipher.encrypt(&nonce, Payload { msg, aad }).unwrap();
+    assert_eq!(ct.len(), msg.len() + 16);
+    let pt = cipher.decrypt(&nonce, Payload { msg: &ct, aad }).unwrap();
+    assert_eq!(&pt[..], &msg[..]);
+    let payload = Payload {
+        msg: &ct,
+        aad: b"footer",
+    };
+    assert!(cipher.decrypt(&nonce, payload).is_err());
+    ct[0] ^= 1;
+    assert!(cipher.decrypt(&nonce, Payload { msg: &ct, aad }).is_err());
+}
+
+#[cfg(feature = "aead")]
+#[test]
+fn test_aead_traits() {
+    use aead::{Aead, KeyInit, Payload};
+    use gcrypt::cipher::aead::*;
+
+    setup();
+
+    check_aead_traits::<Aes128Gcm>();
+    check_aead_traits::<Aes192Gcm>();
+    check_aead_traits::<Aes256Gcm>();
+    check_aead_traits::<Aes128Ccm>();
+    check_aead_traits::<Aes256Ccm>();
+    check_aead_traits::<Aes128Ocb>();
+    check_aead_traits::<Aes256Ocb>();
+    check_aead_traits::<Aes128Eax>();
+    check_aead_traits::<Aes256Eax>();
+    check_aead_traits::<ChaCha20Poly1305>();
+
+    let cipher = Aes128Gcm::new(&Default::default());
+    let ct = cipher.encrypt(&Default::default(), &[0u8; 16][..]).unwrap();
+    assert_eq!(
+        &ct[..],
+        &b"\x03\x88\xda\xce\x60\xb6\xa3\x92\xf3\x28\xc2\xb9\x71\xb2\xfe\x78\
+           \xab\x6e\x47\xd4\x2c\xec\x13\xbd\xf5\x3a\x67\xb2\x12\x57\xbd\xdf"[..]
+    );
+
+    let cipher = ChaCha20Poly1305::new_from_slice(
+        b"\x80\x81\x82\x83\x84\x85\x86\x87\x88\x89\x8a\x8b\x8c\x8d\x8e\x8f\
+           \x90\x91\x92\x93\x94\x95\x96\x97\x98\x99\x9a\x9b\x9c\x9d\x9e\x9f",
+    )
+    .unwrap();
+    let ct = cipher
+        .encrypt(
+            b"\x07\x00\x00\x00\x40\x41\x42\x43\x44\x45\x46\x47".into(),
+            Payload {
+                msg: b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.",
+                aad: b"\x50\x51\x52\x53\xc0\xc1\xc2\xc3\xc4\xc5\xc6\xc7",
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        &ct[..],
+        &b"\xd3\x1a\x8d\x34\x64\x8e\x60\xdb\x7b\x86\xaf\xbc\x53\xef\x7e\xc2\
+           \xa4\xad\xed\x51\x29\x6e\x08\xfe\xa9\xe2\xb5\xa7\x36\xee\x62\xd6\
+           \x3d\xbe\xa4\x5e\x8c\xa9\x67\x12\x82\xfa\xfb\x69\xda\x92\x72\x8b\
+           \x1a\x71\xde\x0a\x9e\x06\x0b\x29\x05\xd6\xa5\xb6\x7e\xcd\x3b\x36\
+           \x92\xdd\xbd\x7f\x2d\x77\x8b\x8c\x98\x03\xae\xe3\x28\x09\x1b\x58\
+           \xfa\xb3\x24\xe4\xfa\xd6\x75\x94\x55\x85\x80\x8b\x48\x31\xd7\xbc\
+           \x3f\xf4\xde\xf0\x8e\x4b\x7a\x9d\xe5\x76\xd2\x65\x86\xce\xc6\x4b\
+           \x61\x16\x1a\xe1\x0b\x59\x4f\x09\xe2\x6a\x7e\x90\x2e\xcb\xd0\x60\
+           \x06\x91"[..]
+    );
+}
+
+#[cfg(feature = "cipher")]
+fn check_block_cipher_traits<C>(algo: CipherAlgorithm)
+where
+    C: ::cipher::BlockEncrypt + ::cipher::BlockDecrypt + ::cipher::KeyInit,
+{
+    use ::cipher::generic_array::GenericArray;
+
+    if !algo.is_available() {
+        return;
+    }
+
+    let key = ::cipher::Key::<C>::from_exact_iter(0..(C::key_size() as u8)).unwrap();
+    let c = C::new(&key);
+    let mut blocks = vec![GenericArray::default(); 19];
+    for (i, b) in blocks.iter_mut().flat_map(|b| b.iter_mut()).enumerate() {
+        *b = i as u8;
+    }
+    let plain = blocks.clone();
+
+    let mut expected = vec![0u8; blocks.len() * C::block_size()];
+    let mut ecb = Cipher::new(algo, CipherMode::Ecb).unwrap();
+    ecb.set_key(&key).unwrap();
+    ecb.encrypt(&plain.concat(), &mut expected).unwrap();
+
+    c.encrypt_blocks(&mut blocks);
+    assert_eq!(blocks.concat(), expected);
+    c.decrypt_blocks(&mut blocks);
+    assert_eq!(blocks, plain);
+
+    let mut block = plain[0].clone();
+    c.encrypt_block(&mut block);
+    assert_eq!(&block[..], &expected[..C::block_size()]);
+    c.decrypt_block(&mut block);
+    assert_eq!(block, plain[0]);
+}
+
+#[cfg(feature = "cipher")]
+#[test]
+fn test_block_cipher_traits() {
+    use ::cipher::{BlockEncrypt, KeyInit};
+    use gcrypt::cipher::block::*;
+
+    setup();
+
+    check_block_cipher_traits::<Aes128>(CipherAlgorithm::Aes128);
+    check_block_cipher_traits::<Aes192>(CipherAlgorithm::Aes192);
+    check_block_cipher_traits::<Aes256>(CipherAlgorithm::Aes256);
+    check_block_cipher_traits::<Camellia128>(CipherAlgorithm::Camellia128);
+    check_block_cipher_traits::<Camellia192>(CipherAlgorithm::Camellia192);
+    check_block_cipher_traits::<Camellia256>(CipherAlgorithm::Camellia256);
+    check_block_cipher_traits::<Serpent128>(CipherAlgorithm::Serpent128);
+    check_block_cipher_traits::<Serpent192>(CipherAlgorithm::Serpent192);
+    check_block_cipher_traits::<Serpent256>(CipherAlgorithm::Serpent256);
+    check_block_cipher_traits::<Twofish128>(CipherAlgorithm::Twofish128);
+    check_block_cipher_traits::<Twofish256>(CipherAlgorithm::Twofish);
+    check_block_cipher_traits::<Sm4>(CipherAlgorithm::Sm4);
+    check_block_cipher_traits::<Seed>(CipherAlgorithm::Seed);
+    check_block_cipher_traits::<Cast5>(CipherAlgorithm::Cast5);
+    check_block_cipher_traits::<Idea>(CipherAlgorithm::Idea);
+    check_block_cipher_traits::<Aria128>(CipherAlgorithm::Aria128);
+    check_block_cipher_traits::<Aria192>(CipherAlgorithm::Aria192);
+    check_block_cipher_traits::<Aria256>(CipherAlgorithm::Aria256);
+
+    let c =
+        Aes128::new_from_slice(b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f")
+            .unwrap();
+    let mut block = *::cipher::Block::<Aes128>::from_slice(
+        b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff",
+    );
+    c.encrypt_block(&mut block);
+    assert_eq!(
+        &block[..],
+        b"\x69\xc4\xe0\xd8\x6a\x7b\x04\x30\xd8\xcd\xb7\x80\x70\xb4\xc5\x5a"
+    );
+}
+
+fn check_cipher_io(algo: CipherAlgorithm, mode: CipherMode) {
+    use std::io::{Read, Write};
+
+    let key = &b"0123456789abcdef.,;/[]{}-=ABCDEF"[..algo.key_len()];
+    let iv = &b"foobar42FOOBAR17"[..algo.block_len()];
+    let open = || {
+        let mut cipher = Cipher::new(algo, mode).unwrap();
+        cipher.set_key(key).unwrap();
+        cipher.set_iv(iv).unwrap();
+        cipher
+    };
+    let padded = (mode == CipherMode::Ecb) || (mode == CipherMode::Cbc);
+
+    for &len in [0, 1, 15, 16, 17, 20000].iter() {
+        let msg: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+
+        let mut encryptor = cipher::Encryptor::new(Vec::new(), open()).unwrap();
+        for chunk in msg.chunks(7) {
+            encryptor.write_all(chunk).unwrap();
+        }
+        let ct = encryptor.finish().unwrap();
+
+        let mut expected = msg.clone();
+        if padded {
+            let n = algo.block_len() - (len % algo.block_len());
+            expected.resize(len + n, n as u8);
+        }
+        open().encrypt_inplace(&mut expected).unwrap();
+        assert_eq!(ct, expected);
+
+        let mut decryptor = cipher::Decryptor::new(&ct[..], open()).unwrap();
+        let mut pt = Vec::new();
+        let mut buf = [0; 5];
+        loop {
+            match decryptor.read(&mut buf).unwrap() {
+                0 => break,
+                n => pt.extend_from_slice(&buf[..n]),
+            }
+        }
+        assert_eq!(pt, msg);
+
+        if padded {
+            let mut decryptor = cipher::Decryptor::new(&ct[..(ct.len() - 1)], open()).unwrap();
+            assert!(decryptor.read_to_end(&mut Vec::new()).is_err());
+        }
+    }
+
+    if padded {
+        let mut ct = Vec::new();
+        {
+            let mut encryptor = cipher::Encryptor::new(&mut ct, open()).unwrap();
+            encryptor.write_all(b"foobar").unwrap();
+            encryptor.finish().unwrap();
+        }
+        let last = ct.len() - 1;
+        ct[last] ^= 0x80;
+        let mut decryptor = cipher::Decryptor::new(&ct[..], open()).unwrap();
+        let mut pt = Vec::new();
+        assert!(decryptor.read_to_end(&mut pt).is_err());
+        assert!(pt.is_empty());
+    }
+}
+
+#[test]
+fn test_cipher_io() {
+    setup();
+
+    check_cipher_io(CipherAlgorithm::Aes128, CipherMode::Ecb);
+    check_cipher_io(CipherAlgorithm::Aes128, CipherMode::Cbc);
+    check_cipher_io(CipherAlgorithm::Aes256, CipherMode::Ctr);
+    check_cipher_io(CipherAlgorithm::Aes256, CipherMode::Cfb);
+    check_cipher_io(CipherAlgorithm::Aes256, CipherMode::Cfb8);
+    check_cipher_io(CipherAlgorithm::Camellia128, CipherMode::Ofb);
+    check_cipher_io(CipherAlgorithm::Cast5, CipherMode::Cbc);
+
+    let cipher = Cipher::new(CipherAlgorithm::Aes128, CipherMode::Gcm).unwrap();
+    assert_eq!(
+        cipher::Encryptor::new(Vec::new(), cipher).unwrap_err(),
+        Error::INV_CIPHER_MODE
+    );
+}
+
+#[test]
+fn test_padding() {
+    use gcrypt::cipher::Padding;
+
+    setup();
+
+    let specs = [
+        (
+            Padding::Pkcs7,
+            &b"\x03\x03\x03"[..],
+            &b"\x08\x08\x08\x08\x08\x08\x08\x08"[..],
+        ),
+        (
+            Padding::Iso7816,
+            b"\x80\x00\x00",
+            b"\x80\x00\x00\x00\x00\x00\x00\x00",
+        ),
+        (
+            Padding::AnsiX923,
+            b"\x00\x00\x03",
+            b"\x00\x00\x00\x00\x00\x00\x00\x08",
+        ),
+        (Padding::Zero, b"\x00\x00\x00", b""),
+    ];
+    for &(padding, short, full) in specs.iter() {
+        let mut buf = b"01234".to_vec();
+        padding.pad(&mut buf, 8);
+        assert_eq!(&buf[5..], short);
+        assert_eq!(padding.unpad(&buf, 8).unwrap(), 5);
+
+        let mut buf = b"01234567".to_vec();
+        padding.pad(&mut buf, 8);
+        assert_eq!(&buf[8..], full);
+        assert_eq!(padding.unpad(&buf, 8).unwrap(), 8);
+
+        assert!(padding.unpad(b"0123456", 8).is_err());
+    }
+
+    let malformed = [
+        (Padding::Pkcs7, &b"01234567"[..]),
+        (Padding::Pkcs7, b"01234\x00\x00\x00"),
+        (Padding::Pkcs7, b"01234\x02\x03\x03"),
+        (Padding::Pkcs7, b"01234\x09\x09\x09"),
+        (Padding::Pkcs7, b""),
+        (Padding::Iso7816, b"01234\x00\x00\x00"),
+        (Padding::Iso7816, b"01234\x80\x00\x01"),
+        (Padding::Iso7816, b"\x00\x00\x00\x00\x00\x00\x00\x00"),
+        (Padding::AnsiX923, b"01234\x00\x01\x03"),
+        (Padding::AnsiX923, b"01234\x00\x00\x00"),
+        (Padding::AnsiX923, b"01234\x00\x00\x09"),
+    ];
+    for &(padding, buf) in malformed.iter() {
+        assert_eq!(
+            padding.unpad(buf, 8).unwrap_err().code(),
+            Error::DECRYPT_FAILED.code()
+        );
+    }
+    assert_eq!(Padding::Zero.unpad(b"", 8).unwrap(), 0);
+
+    let algo = CipherAlgorithm::Aes128;
+    let open = || {
+        let mut cipher = Cipher::new(algo, CipherMode::Cbc).unwrap();
+        cipher.set_key(b"0123456789abcdef").unwrap();
+        cipher.set_iv(b"foobar42FOOBAR17").unwrap();
+        cipher
+    };
+    for &(padding, _, _) in specs.iter() {
+        for &len in [0, 1, 15, 16, 17].iter() {
+            let msg = vec![0x42; len];
+            let ct = open().encrypt_padded(padding, &msg).unwrap();
+            assert_eq!(ct.len() % 16, 0);
+            assert!(ct.len() > len || padding == Padding::Zero);
+            assert_eq!(open().decrypt_padded(padding, &ct).unwrap(), msg);
+        }
+    }
+    let mut ct = open().encrypt_padded(Padding::Pkcs7, b"foobar").unwrap();
+    ct[15] ^= 0x80;
+    assert_eq!(
+        open()
+            .decrypt_padded(Padding::Pkcs7, &ct)
+            .unwrap_err()
+            .code(),
+        Error::DECRYPT_FAILED.code()
+    );
+
+    {
+        use std::io::{Read, Write};
+
+        let mut encryptor =
+            cipher::Encryptor::with_padding(Vec::new(), open(), Padding::Iso7816).unwrap();
+        encryptor.write_all(&[0x42; 40]).unwrap();
+        let ct = encryptor.finish().unwrap();
+        assert_eq!(
+            ct,
+            open()
+                .encrypt_padded(Padding::Iso7816, &[0x42; 40])
+                .unwrap()
+        );
+
+        let mut decryptor =
+            cipher::Decryptor::with_padding(&ct[..], open(), Padding::Iso7816).unwrap();
+        let mut pt = Vec::new();
+        decryptor.read_to_end(&mut pt).unwrap();
+        assert_eq!(pt, &[0x42; 40][..]);
+    }
+}
+
+#[test]
+fn test_cipher_ctl() {
+    use gcrypt::cipher::aead;
+
+    setup();
+
+    let key = b"0123456789abcdef";
+    let nonce = b"foobar42FOOB";
+    let msg = b"0123456789abcdef0123";
+
+    let mut cipher = Cipher::new(CipherAlgorithm::Aes128, CipherMode::Ccm).unwrap();
+    cipher.set_key(key).unwrap();
+    cipher.set_iv(nonce).unwrap();
+    cipher.set_ccm_lengths(msg.len() as u64, 6, 16).unwrap();
+    assert_eq!(cipher.tag_len().unwrap(), 16);
+    cipher.authenticate(b"header").unwrap();
+    let mut sealed = msg.to_vec();
+    cipher.encrypt_inplace(&mut sealed).unwrap();
+    let mut tag = [0; 16];
+    cipher.get_tag(&mut tag).unwrap();
+    sealed.extend_from_slice(&tag);
+    assert_eq!(
+        sealed,
+        aead::seal(
+            CipherAlgorithm::Aes128,
+            CipherMode::Ccm,
+            key,
+            nonce,
+            b"header",
+            msg
+        )
+        .unwrap()
+    );
+
+    let mut cipher = Cipher::new(CipherAlgorithm::Aes128, CipherMode::Ccm).unwrap();
+    cipher.set_key(key).unwrap();
+    cipher.set_iv(nonce).unwrap();
+    assert!(cipher.set_ccm_lengths(0, 0, 5).is_err());
+    cipher.set_ccm_lengths(0, 0, 8).unwrap();
+    assert_eq!(cipher.tag_len().unwrap(), 8);
+
+    let mut cipher = Cipher::new(CipherAlgorithm::Aes128, CipherMode::Ocb).unwrap();
+    cipher.set_key(key).unwrap();
+    cipher.set_iv(nonce).unwrap();
+    assert_eq!(cipher.tag_len().unwrap(), 16);
+    cipher.set_tag_len(8).unwrap();
+    assert_eq!(cipher.tag_len().unwrap(), 8);
+    assert!(cipher.set_tag_len(7).is_err());
+    cipher.finalize().unwrap();
+    let mut buf = *msg;
+    cipher.encrypt_inplace(&mut buf).unwrap();
+    let mut tag = [0; 8];
+    cipher.get_tag(&mut tag).unwrap();
+
+    let mut cipher = Cipher::new(CipherAlgorithm::Aes128, CipherMode::Gcm).unwrap();
+    assert_eq!(
+        cipher.set_ccm_lengths(0, 0, 16).unwrap_err().code(),
+        Error::INV_CIPHER_MODE.code()
+    );
+    assert_eq!(
+        cipher.set_tag_len(16).unwrap_err().code(),
+        Error::INV_CIPHER_MODE.code()
+    );
+    let mut cipher = Cipher::new(CipherAlgorithm::Aes128, CipherMode::Cbc).unwrap();
+    assert_eq!(cipher.mode(), Some(CipherMode::Cbc));
+    assert_eq!(cipher.algorithm(), Some(CipherAlgorithm::Aes128));
+    assert_eq!(cipher.tag_len(), Err(Error::INV_CIPHER_MODE));
+    assert_eq!(
+        cipher.set_ccm_lengths(0, 0, 16),
+        Err(Error::INV_CIPHER_MODE)
+    );
+    assert_eq!(cipher.set_tag_len(16), Err(Error::INV_CIPHER_MODE));
+
+    let weak = b"\x01\x01\x01\x01\x01\x01\x01\x01";
+    let mut cipher = Cipher::new(CipherAlgorithm::Des, CipherMode::Ecb).unwrap();
+    assert_eq!(
+        cipher.set_key(weak).unwrap_err().code(),
+        Error::WEAK_KEY.code()
+    );
+    assert!(cipher.encrypt_inplace(&mut [0; 8]).is_err());
+    let mut cipher = Cipher::new(CipherAlgorithm::Des, CipherMode::Ecb).unwrap();
+    cipher.allow_weak_key(true).unwrap();
+    assert_eq!(
+        cipher.set_key(weak).unwrap_err().code(),
+        Error::WEAK_KEY.code()
+    );
+    cipher.encrypt_inplace(&mut [0; 8]).unwrap();
+
+    if CipherAlgorithm::Gost28147.is_available() {
+        let mut cipher = Cipher::new(CipherAlgorithm::Gost28147, CipherMode::Ecb).unwrap();
+        cipher.set_sbox("1.2.643.2.2.31.1").unwrap();
+        assert!(cipher.set_sbox("1.2.3.4").is_err());
+    }
+}
+
+#[test]
+fn test_xts_sectors() {
+    use gcrypt::cipher::xts::SectorCipher;
+
+    setup();
+
+    let key = b"\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\
+                \x22\x22\x22\x22\x22\x22\x22\x22\x22\x22\x22\x22\x22\x22\x22\x22";
+    let mut xts = SectorCipher::new(CipherAlgorithm::Aes128, key, 32).unwrap();
+    assert_eq!(xts.sector_size(), 32);
+    let mut buf = [0x44; 32];
+    xts.encrypt_sectors(0x33_3333_3333, &mut buf).unwrap();
+    assert_eq!(
+        &buf[..],
+        &b"\xc4\x54\x18\x5e\x6a\x16\x93\x6e\x39\x33\x40\x38\xac\xef\x83\x8b\
+           \xfb\x18\x6f\xff\x74\x80\xad\xc4\x28\x93\x82\xec\xd6\xd3\x94\xf0"[..]
+    );
+    xts.decrypt_sectors(0x33_3333_3333, &mut buf).unwrap();
+    assert_eq!(buf, [0x44; 32]);
+
+    let key: Vec<u8> = (0..64).collect();
+    let mut xts = SectorCipher::new(CipherAlgorithm::Aes256, &key, 512).unwrap();
+    let msg: Vec<u8> = (0..(3 * 512)).map(|i| i as u8).collect();
+    let mut buf = msg.clone();
+    xts.encrypt_sectors(5, &mut buf).unwrap();
+    for (i, sector) in buf.chunks(512).enumerate() {
+        let mut cipher = Cipher::new(CipherAlgorithm::Aes256, CipherMode::Xts).unwrap();
+        cipher.set_key(&key).unwrap();
+        let mut tweak = [0; 16];
+        tweak[0] = 5 + i as u8;
+        cipher.set_iv(tweak).unwrap();
+        let mut expected = msg[(i * 512)..((i + 1) * 512)].to_vec();
+        cipher.encrypt_inplace(&mut expected).unwrap();
+        assert_eq!(sector, &expected[..]);
+    }
+    xts.decrypt_sectors(5, &mut buf).unwrap();
+    assert_eq!(buf, msg);
+
+    assert!(xts.encrypt_sectors(0, &mut [0; 100]).is_err());
+    assert!(xts.encrypt_sectors(u64::MAX, &mut [0; 1024]).is_err());
+    xts.encrypt_sectors(u64::MAX, &mut [0; 512]).unwrap();
+
+    assert_eq!(
+        SectorCipher::new(CipherAlgorithm::Aes128, &[0x42; 32], 512)
+            .unwrap_err()
+            .code(),
+        Error::WEAK_KEY.code()
+    );
+    assert!(SectorCipher::new(CipherAlgorithm::Aes128, &key, 512).is_err());
+    assert!(SectorCipher::new(CipherAlgorithm::Aes128, &key[..32], 500).is_err());
+}
+
+#[test]
+fn test_keywrap() {
+    use gcrypt::cipher::keywrap;
+
+    setup();
+
+    let kek = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f";
+    let key = b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff";
+    let mut wrapped = keywrap::wrap(kek, key).unwrap();
+    assert_eq!(
+        &wrapped[..],
+        &b"\x1f\xa6\x8b\x0a\x81\x12\xb4\x47\xae\xf3\x4b\xd8\xfb\x5a\x7b\x82\
+           \x9d\x3e\x86\x23\x71\xd2\xcf\xe5"[..]
+    );
+    assert_eq!(keywrap::unwrap(kek, &wrapped).unwrap(), &key[..]);
+    wrapped[0] ^= 1;
+    assert_eq!(keywrap::unwrap(kek, &wrapped).unwrap_err(), Error::CHECKSUM);
+    assert!(keywrap::wrap(kek, &key[..12]).is_err());
+    assert!(keywrap::wrap(&kek[..15], key).is_err());
+    assert!(keywrap::unwrap(kek, &wrapped[..20]).is_err());
+
+    if !gcrypt::init_default().check_version("1.9.0") {
+        return;
+    }
+
+    let kek = b"\x58\x40\xdf\x6e\x29\xb0\x2a\xf1\xab\x49\x3b\x70\x5b\xf1\x6e\xa1\
+                \xae\x83\x38\xf4\xdc\xc1\x76\xa8";
+    let key = b"\xc3\x7b\x7e\x64\x92\x58\x43\x40\xbe\xd1\x22\x07\x80\x89\x41\x15\
+                \x50\x68\xf7\x38";
+    let mut wrapped = keywrap::wrap_pad(kek, key).unwrap();
+    assert_eq!(
+        &wrapped[..],
+        &b"\x13\x8b\xde\xaa\x9b\x8f\xa7\xfc\x61\xf9\x77\x42\xe7\x22\x48\xee\
+           \x5a\xe6\xae\x53\x60\xd1\xae\x6a\x5f\x54\xf3\x73\xfa\x54\x3b\x6a"[..]
+    );
+    assert_eq!(keywrap::unwrap_pad(kek, &wrapped).unwrap(), &key[..]);
+    wrapped[31] ^= 1;
+    assert_eq!(
+        keywrap::unwrap_pad(kek, &wrapped).unwrap_err(),
+        Error::CHECKSUM
+    );
+
+    let wrapped = keywrap::wrap_pad(kek, b"\x46\x6f\x72\x50\x61\x73\x69").unwrap();
+    assert_eq!(
+        &wrapped[..],
+        &b"\xaf\xbe\xb0\xf0\x7d\xfb\xf5\x41\x92\x00\xf2\xcc\xb5\x0b\xb2\x4f"[..]
+    );
+    assert_eq!(
+        keywrap::unwrap_pad(kek, &wrapped).unwrap(),
+        &b"\x46\x6f\x72\x50\x61\x73\x69"[..]
+    );
+    assert!(keywrap::wrap_pad(kek, b"").is_err());
+}
+
+#[test]
+fn test_siv() {
+    use gcrypt::cipher::{aead, siv};
+
+    setup();
+
+    if !gcrypt::init_default().check_version("1.10.0") {
+        return;
+    }
+
+    let key = b"\xff\xfe\xfd\xfc\xfb\xfa\xf9\xf8\xf7\xf6\xf5\xf4\xf3\xf2\xf1\xf0\
+                \xf0\xf1\xf2\xf3\xf4\xf5\xf6\xf7\xf8\xf9\xfa\xfb\xfc\xfd\xfe\xff";
+    let ad = b"\x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1a\x1b\x1c\x1d\x1e\x1f\
+               \x20\x21\x22\x23\x24\x25\x26\x27";
+    let msg = b"\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee";
+    let mut sealed = siv::seal(CipherAlgorithm::Aes128, key, &[ad], msg).unwrap();
+    assert_eq!(
+        &sealed[..],
+        &b"\x85\x63\x2d\x07\xc6\xe8\xf3\x7f\x95\x0a\xcd\x32\x0a\x2e\xcc\x93\
+           \x40\xc0\x2b\x96\x90\xc4\xdc\x04\xda\xef\x7f\x6a\xfe\x5c"[..]
+    );
+    assert_eq!(
+        siv::open(CipherAlgorithm::Aes128, key, &[ad], &sealed).unwrap(),
+        &msg[..]
+    );
+    assert!(siv::open(CipherAlgorithm::Aes128, key, &[], &sealed).is_err());
+    assert!(siv::open(CipherAlgorithm::Aes128, key, &[ad], &sealed[..15]).is_err());
+    sealed[20] ^= 1;
+    assert_eq!(
+        siv::open(CipherAlgorithm::Aes128, key, &[ad], &sealed)
+            .unwrap_err()
+            .code(),
+        Error::CHECKSUM.code()
+    );
+
+    let key = b"\x7f\x7e\x7d\x7c\x7b\x7a\x79\x78\x77\x76\x75\x74\x73\x72\x71\x70\
+                \x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4a\x4b\x4c\x4d\x4e\x4f";
+    let aad: [&[u8]; 3] = [
+        b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff\
+          \xde\xad\xda\xda\xde\xad\xda\xda\xff\xee\xdd\xcc\xbb\xaa\x99\x88\
+          \x77\x66\x55\x44\x33\x22\x11\x00",
+        b"\x10\x20\x30\x40\x50\x60\x70\x80\x90\xa0",
+        b"\x09\xf9\x11\x02\x9d\x74\xe3\x5b\xd8\x41\x56\xc5\x63\x56\x88\xc0",
+    ];
+    let msg = b"this is some plaintext to encrypt using SIV-AES";
+    let sealed = siv::seal(CipherAlgorithm::Aes128, key, &aad, msg).unwrap();
+    assert_eq!(
+        &sealed[..],
+        &b"\x7b\xdb\x6e\x3b\x43\x26\x67\xeb\x06\xf4\xd1\x4b\xff\x2f\xbd\x0f\
+           \xcb\x90\x0f\x2f\xdd\xbe\x40\x43\x26\x60\x19\x65\xc8\x89\xbf\x17\
+           \xdb\xa7\x7c\xeb\x09\x4f\xa6\x63\xb7\xa3\xf7\x48\xba\x8a\xf8\x29\
+           \xea\x64\xad\x54\x4a\x27\x2e\x9c\x48\x5b\x62\xa3\xfd\x5c\x0d"[..]
+    );
+    assert_eq!(
+        siv::open(CipherAlgorithm::Aes128, key, &aad, &sealed).unwrap(),
+        &msg[..]
+    );
+
+    let sealed = aead::seal(
+        CipherAlgorithm::Aes128,
+        CipherMode::GcmSiv,
+        b"\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
+        b"\x03\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
+        b"",
+        b"",
+    )
+    .unwrap();
+    assert_eq!(
+        &sealed[..],
+        &b"\xdc\x20\xe2\xd8\x3f\x25\x70\x5b\xb4\x9e\x43\x9e\xca\x56\xde\x25"[..]
+    );
+
+    for &mode in [CipherMode::Siv, CipherMode::GcmSiv].iter() {
+        let key = &key[..(if mode == CipherMode::Siv { 32 } else { 16 })];
+        let nonce = b"foobar42FOOB";
+        let mut sealed =
+            aead::seal(CipherAlgorithm::Aes128, mode, key, nonce, b"header", msg).unwrap();
+        assert_eq!(
+            aead::open(
+                CipherAlgorithm::Aes128,
+                mode,
+                key,
+                nonce,
+                b"header",
+                &sealed
+            )
+            .unwrap(),
+            &msg[..]
+        );
+        sealed[0] ^= 1;
+        assert!(aead::open(
+            CipherAlgorithm::Aes128,
+            mode,
+            key,
+            nonce,
+            b"header",
+            &sealed
+        )
+        .is_err());
+    }
+}
+
+fn check_cipher_macs(
+    algo: CipherAlgorithm, cmac: gcrypt::mac::Algorithm, gmac: gcrypt::mac::Algorithm,
+    poly1305: gcrypt::mac::Algorithm,
+) {
+    use gcrypt::mac::{Algorithm as MacAlgorithm, Mac};
+
+    if !algo.is_available() {
+        return;
+    }
+
+    let key = b"0123456789abcdef";
+    let data = b"foobar42FOOBAR17";
+    let encrypt = |block: &[u8]| {
+        let mut cipher = Cipher::new(algo, CipherMode::Ecb).unwrap();
+        cipher.set_key(key).unwrap();
+        let mut output = block.to_vec();
+        cipher.encrypt_inplace(&mut output).unwrap();
+        output
+    };
+    let mac = |algo: MacAlgorithm, key: &[u8], iv: Option<&[u8]>| {
+        let mut mac = Mac::new(algo).unwrap();
+        mac.set_key(key).unwrap();
+        if let Some(iv) = iv {
+            mac.set_iv(iv).unwrap();
+        }
+        mac.update(data).unwrap();
+        let mut tag = [0; 16];
+        assert_eq!(mac.get_mac(&mut tag).unwrap(), 16);
+        tag
+    };
+
+    if cmac.is_available() {
+        let mut k1 = encrypt(&[0; 16]);
+        let carry = k1[0] >> 7;
+        for i in 0..15 {
+            k1[i] = (k1[i] << 1) | (k1[i + 1] >> 7);
+        }
+        k1[15] = (k1[15] << 1) ^ (carry * 0x87);
+        let block: Vec<u8> = data.iter().zip(k1.iter()).map(|(a, b)| a ^ b).collect();
+        assert_eq!(&mac(cmac, key, None)[..], &encrypt(&block)[..]);
+    }
+
+    if gmac.is_available() {
+        let iv = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b";
+        let expected = cipher::aead::seal(algo, CipherMode::Gcm, key, iv, data, b"").unwrap();
+        assert_eq!(&mac(gmac, key, Some(iv))[..], &expected[..]);
+    }
+
+    if poly1305.is_available() {
+        let r = b"\x85\xd6\xbe\x78\x57\x55\x6d\x33\x7f\x44\x52\xfe\x42\xd5\x06\xa8";
+        let nonce = b"\xfb\x44\x73\x50\xc4\xe8\x68\xc5\x2a\xc3\x27\x5c\xf9\xd4\x32\x7e";
+        let mut cipher_key = key.to_vec();
+        cipher_key.extend_from_slice(r);
+        let mut raw_key = r.to_vec();
+        raw_key.extend_from_slice(&encrypt(nonce));
+        assert_eq!(
+            mac(poly1305, &cipher_key, Some(nonce)),
+            mac(MacAlgorithm::Poly1305, &raw_key, None)
+        );
+    }
+}
+
+#[test]
+fn test_cipher_algorithms() {
+    use gcrypt::mac::Algorithm as MacAlgorithm;
+
+    setup();
+
+    let key = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f\
+                \x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1a\x1b\x1c\x1d\x1e\x1f";
+    let specs = [
+        (
+            CipherAlgorithm::Aria128,
+            &b"\xd7\x18\xfb\xd6\xab\x64\x4c\x73\x9d\xa9\x5f\x3b\xe6\x45\x17\x78"[..],
+        ),
+        (
+            CipherAlgorithm::Aria192,
+            b"\x26\x44\x9c\x18\x05\xdb\xe7\xaa\x25\xa4\x68\xce\x26\x3a\x9e\x79",
+        ),
+        (
+            CipherAlgorithm::Aria256,
+            b"\xf9\x2b\xd7\xc7\x9f\xb7\x2e\x2f\x2b\x8f\x80\xc1\x97\x2d\x24\xfc",
+        ),
+    ];
+    for &(algo, expected) in specs.iter() {
+        if !algo.is_available() {
+            continue;
+        }
+        let mut cipher = Cipher::new(algo, CipherMode::Ecb).unwrap();
+        cipher.set_key(&key[..algo.key_len()]).unwrap();
+        let mut buf = *b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff";
+        cipher.encrypt_inplace(&mut buf).unwrap();
+        assert_eq!(&buf[..], expected);
+        cipher.decrypt_inplace(&mut buf).unwrap();
+        assert_eq!(
+            &buf[..],
+            &b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff"[..]
+        );
+    }
+
+    check_cipher_macs(
+        CipherAlgorithm::Aes128,
+        MacAlgorithm::CmacAes,
+        MacAlgorithm::GmacAes,
+        MacAlgorithm::Poly1305Aes,
+    );
+    check_cipher_macs(
+        CipherAlgorithm::Sm4,
+        MacAlgorithm::CmacSm4,
+        MacAlgorithm::GmacSm4,
+        MacAlgorithm::Poly1305Sm4,
+    );
+    check_cipher_macs(
+        CipherAlgorithm::Aria128,
+        MacAlgorithm::CmacAria,
+        MacAlgorithm::GmacAria,
+        MacAlgorithm::Poly1305Aria,
+    );
+}
+
+#[test]
+fn test_envelope() {
+    use gcrypt::envelope::{self, Header, Limits, Opener, Params, Sealer};
+    use std::io::{Read, Write};
+
+    setup();
+
+    let mut params = Params::default();
+    params.scrypt(1024, 1).chunk_size(16);
+    for &len in [0, 5, 16, 33, 100].iter() {
+        let msg: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let sealed = envelope::seal_with_password(&params, b"password", &msg).unwrap();
+        assert_eq!(
+            envelope::open_with_password(b"password", &sealed).unwrap(),
+            msg
+        );
+        assert!(envelope::open_with_password(b"passw0rd", &sealed).is_err());
+        assert!(envelope::open_with_password(b"password", &sealed[..(sealed.len() - 1)]).is_err());
+        if len > 16 {
+            let header_len = Header::read_from(&sealed[..]).unwrap().to_bytes().len();
+            let mut truncated = sealed[..(header_len + 32)].to_vec();
+            assert!(envelope::open_with_password(b"password", &truncated).is_err());
+            truncated[header_len + 31] ^= 1;
+            assert!(envelope::open_with_password(b"password", &truncated).is_err());
+        }
+    }
+
+    let sealed = envelope::seal_with_password(&params, b"password", b"foobar").unwrap();
+    let header = Header::read_from(&sealed[..]).unwrap();
+    assert_eq!(header.kdf(), gcrypt::kdf::Algorithm::Scrypt);
+    assert_eq!(header.kdf_subalgo(), 1024);
+    assert_eq!(header.kdf_iterations(), 1);
+    assert_eq!(header.cipher(), CipherAlgorithm::Aes256);
+    assert_eq!(header.mode(), CipherMode::Gcm);
+    assert_eq!(header.nonce().len(), 12);
+    assert_eq!(header.chunk_size(), 16);
+    assert_eq!(&sealed[..header.to_bytes().len()], &header.to_bytes()[..]);
+    let mut tampered = sealed.clone();
+    tampered[20] ^= 1;
+    assert!(envelope::open_with_password(b"password", &tampered).is_err());
+    tampered[..8].copy_from_slice(b"NOTENVEL");
+    assert_eq!(
+        envelope::open_with_password(b"password", &tampered).unwrap_err(),
+        Error::INV_DATA
+    );
+
+    let mut params = Params::default();
+    params
+        .pbkdf2(DigestAlgorithm::Sha256, 1000)
+        .cipher(CipherAlgorithm::Chacha20, CipherMode::Poly1305)
+        .chunk_size(1000);
+    let msg: Vec<u8> = (0..10000).map(|i| (i * 7) as u8).collect();
+    let mut sealer = Sealer::new(Vec::new(), &params, b"secret").unwrap();
+    assert_eq!(sealer.header().kdf(), gcrypt::kdf::Algorithm::Pbkdf2);
+    for chunk in msg.chunks(7) {
+        sealer.write_all(chunk).unwrap();
+    }
+    let sealed = sealer.finish().unwrap();
+    let mut opener = Opener::new(&sealed[..], b"secret").unwrap();
+    assert_eq!(opener.header().mode(), CipherMode::Poly1305);
+    let mut output = Vec::new();
+    let mut buf = [0; 5];
+    loop {
+        match opener.read(&mut buf).unwrap() {
+            0 => break,
+            n => output.extend_from_slice(&buf[..n]),
+        }
+    }
+    assert_eq!(output, msg);
+
+    params.cipher(CipherAlgorithm::Aes128, CipherMode::Cbc);
+    assert!(envelope::seal_with_password(&params, b"secret", b"").is_err());
+    params.cipher(CipherAlgorithm::Aes128, CipherMode::Poly1305);
+    assert_eq!(
+        envelope::seal_with_password(&params, b"secret", b"").unwrap_err(),
+        Error::INV_CIPHER_MODE
+    );
+    params.cipher(CipherAlgorithm::Chacha20, CipherMode::Gcm);
+    assert_eq!(
+        envelope::seal_with_password(&params, b"secret", b"").unwrap_err(),
+        Error::INV_CIPHER_MODE
+    );
+    params
+        .cipher(CipherAlgorithm::Aes128, CipherMode::Ccm)
+        .chunk_size(1 << 24);
+    assert_eq!(
+        envelope::seal_with_password(&params, b"secret", b"").unwrap_err(),
+        Error::INV_ARG
+    );
+    params.chunk_size((1 << 24) - 1);
+    let sealed = envelope::seal_with_password(&params, b"secret", b"foobar").unwrap();
+    assert_eq!(
+        envelope::open_with_password(b"secret", &sealed).unwrap(),
+        b"foobar"
+    );
+    params.pbkdf2(DigestAlgorithm::Sha256, 0);
+    assert_eq!(
+        envelope::seal_with_password(&params, b"secret", b"").unwrap_err(),
+        Error::INV_VALUE
+    );
+
+    // The KDF cost is read from the untrusted header and must be bounded before it is used.
+    let mut params = Params::default();
+    params.scrypt(1024, 1);
+    let sealed = envelope::seal_with_password(&params, b"password", b"foobar").unwrap();
+    let mut limits = Limits::default();
+    limits.scrypt(512, 1);
+    assert_eq!(
+        Opener::with_limits(&sealed[..], b"password", &limits).unwrap_err(),
+        Error::TOO_LARGE
+    );
+    limits.scrypt(1024, 1);
+    assert!(Opener::with_limits(&sealed[..], b"password", &limits).is_ok());
+    let mut tampered = sealed.clone();
+    tampered[13..17].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(
+        envelope::open_with_password(b"password", &tampered).unwrap_err(),
+        Error::INV_VALUE
+    );
+    tampered[13..17].copy_from_slice(&(1u32 << 31).to_be_bytes());
+    assert_eq!(
+        envelope::open_with_password(b"password", &tampered).unwrap_err(),
+        Error::TOO_LARGE
+    );
+    tampered[13..17].copy_from_slice(&1024u32.to_be_bytes());
+    tampered[17..21].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(
+        envelope::open_with_password(b"password", &tampered).unwrap_err(),
+        Error::TOO_LARGE
+    );
+    tampered[17..21].copy_from_slice(&0u32.to_be_bytes());
+    assert_eq!(
+        envelope::open_with_password(b"password", &tampered).unwrap_err(),
+        Error::INV_VALUE
+    );
+
+    let mut params = Params::default();
+    params.pbkdf2(DigestAlgorithm::Sha256, 2000);
+    let sealed = envelope::seal_with_password(&params, b"password", b"").unwrap();
+    let mut limits = Limits::default();
+    limits.pbkdf2(1000);
+    assert_eq!(
+        Header::read_with_limits(&sealed[..], &limits).unwrap_err(),
+        Error::TOO_LARGE
+    );
+    tampered = sealed.clone();
+    tampered[17..21].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(
+        Header::read_from(&tampered[..]).unwrap_err(),
+        Error::TOO_LARGE
+    );
+
+    // Malformed headers are rejected before any key derivation.
+    for &digest in [DigestAlgorithm::Shake128, DigestAlgorithm::Shake256].iter() {
+        tampered = sealed.clone();
+        tampered[13..17].copy_from_slice(&(digest.raw() as u32).to_be_bytes());
+        assert_eq!(
+            Header::read_from(&tampered[..]).unwrap_err(),
+            Error::DIGEST_ALGO
+        );
+    }
+    tampered = sealed.clone();
+    tampered[13..17].copy_from_slice(&12345u32.to_be_bytes());
+    assert_eq!(
+        envelope::open_with_password(b"password", &tampered).unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+    params.pbkdf2(DigestAlgorithm::Shake128, 1000);
+    assert_eq!(
+        envelope::seal_with_password(&params, b"password", b"").unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+    assert_eq!(sealed[21], 32);
+    for &salt_len in [0, 8, 15, 16, 64, 65, 255].iter() {
+        let mut resized = sealed[..21].to_vec();
+        resized.push(salt_len as u8);
+        resized.resize(22 + salt_len, 0x42);
+        resized.extend_from_slice(&sealed[54..]);
+        let result = Header::read_from(&resized[..]).map(|h| h.salt().len());
+        if (16..=64).contains(&salt_len) {
+            assert_eq!(result, Ok(salt_len));
+        } else {
+            assert_eq!(result, Err(Error::INV_LENGTH));
+        }
+    }
+}
+
 fn check_digest(algo: DigestAlgorithm, data: &[u8], expected: &[u8]) {
     let mut digest = MessageDigest::new(algo).unwrap();
     if data.starts_with(b"!") && data.len() == 1 {
@@ -695,34 +1750,1074 @@ fn test_digests() {
            \x5d\xd0\x51\x02\x6b\xb1\x49\xa4\x52\xfd\x84\xe5\xe5\x7b\x55\x00",
         ),
         (
-            DigestAlgorithm::Stribog512,
-            b"\xd1\xe5\x20\xe2\xe5\xf2\xf0\xe8\x2c\x20\xd1\xf2\xf0\xe8\xe1\xee\xe6\xe8\
-           \x20\xe2\xed\xf3\xf6\xe8\x2c\x20\xe2\xe5\xfe\xf2\xfa\x20\xf1\x20\xec\xee\
-           \xf0\xff\x20\xf1\xf2\xf0\xe5\xeb\xe0\xec\xe8\x20\xed\xe0\x20\xf5\xf0\xe0\
-           \xe1\xf0\xfb\xff\x20\xef\xeb\xfa\xea\xfb\x20\xc8\xe3\xee\xf0\xe5\xe2\xfb",
-            b"\x1e\x88\xe6\x22\x26\xbf\xca\x6f\x99\x94\xf1\xf2\xd5\x15\x69\xe0\
-           \xda\xf8\x47\x5a\x3b\x0f\xe6\x1a\x53\x00\xee\xe4\x6d\x96\x13\x76\
-           \x03\x5f\xe8\x35\x49\xad\xa2\xb8\x62\x0f\xcd\x7c\x49\x6c\xe5\xb3\
-           \x3f\x0c\xb9\xdd\xdc\x2b\x64\x60\x14\x3b\x03\xda\xba\xc9\xfb\x28",
+            DigestAlgorithm::Stribog512,
+            b"\xd1\xe5\x20\xe2\xe5\xf2\xf0\xe8\x2c\x20\xd1\xf2\xf0\xe8\xe1\xee\xe6\xe8\
+           \x20\xe2\xed\xf3\xf6\xe8\x2c\x20\xe2\xe5\xfe\xf2\xfa\x20\xf1\x20\xec\xee\
+           \xf0\xff\x20\xf1\xf2\xf0\xe5\xeb\xe0\xec\xe8\x20\xed\xe0\x20\xf5\xf0\xe0\
+           \xe1\xf0\xfb\xff\x20\xef\xeb\xfa\xea\xfb\x20\xc8\xe3\xee\xf0\xe5\xe2\xfb",
+            b"\x1e\x88\xe6\x22\x26\xbf\xca\x6f\x99\x94\xf1\xf2\xd5\x15\x69\xe0\
+           \xda\xf8\x47\x5a\x3b\x0f\xe6\x1a\x53\x00\xee\xe4\x6d\x96\x13\x76\
+           \x03\x5f\xe8\x35\x49\xad\xa2\xb8\x62\x0f\xcd\x7c\x49\x6c\xe5\xb3\
+           \x3f\x0c\xb9\xdd\xdc\x2b\x64\x60\x14\x3b\x03\xda\xba\xc9\xfb\x28",
+        ),
+        (
+            DigestAlgorithm::Stribog256,
+            b"\xd1\xe5\x20\xe2\xe5\xf2\xf0\xe8\x2c\x20\xd1\xf2\xf0\xe8\xe1\xee\xe6\xe8\
+           \x20\xe2\xed\xf3\xf6\xe8\x2c\x20\xe2\xe5\xfe\xf2\xfa\x20\xf1\x20\xec\xee\
+           \xf0\xff\x20\xf1\xf2\xf0\xe5\xeb\xe0\xec\xe8\x20\xed\xe0\x20\xf5\xf0\xe0\
+           \xe1\xf0\xfb\xff\x20\xef\xeb\xfa\xea\xfb\x20\xc8\xe3\xee\xf0\xe5\xe2\xfb",
+            b"\x9d\xd2\xfe\x4e\x90\x40\x9e\x5d\xa8\x7f\x53\x97\x6d\x74\x05\xb0\
+           \xc0\xca\xc6\x28\xfc\x66\x9a\x74\x1d\x50\x06\x3c\x55\x7e\x8f\x50",
+        ),
+    ];
+
+    for spec in specs {
+        if !spec.0.is_available() {
+            continue;
+        }
+
+        check_digest(spec.0, spec.1, spec.2);
+    }
+}
+
+#[test]
+fn test_xof() {
+    use std::io::Read;
+
+    setup();
+
+    let specs: &[(DigestAlgorithm, &[u8], &[u8])] = &[
+        (
+            DigestAlgorithm::Shake128,
+            b"",
+            b"\x7f\x9c\x2b\xa4\xe8\x8f\x82\x7d\x61\x60\x45\x50\x76\x05\x85\x3e\
+              \xd7\x3b\x80\x93\xf6\xef\xbc\x88\xeb\x1a\x6e\xac\xfa\x66\xef\x26",
+        ),
+        (
+            DigestAlgorithm::Shake256,
+            b"",
+            b"\x46\xb9\xdd\x2b\x0b\xa8\x8d\x13\x23\x3b\x3f\xeb\x74\x3e\xeb\x24\
+              \x3f\xcd\x52\xea\x62\xb8\x1b\x82\xb5\x0c\x27\x64\x6e\xd5\x76\x2f",
+        ),
+    ];
+    for spec in specs {
+        if !spec.0.is_available() {
+            continue;
+        }
+
+        let mut digest = MessageDigest::new(spec.0).unwrap();
+        assert_eq!(digest.get_only_digest(), None);
+        digest.update(spec.1);
+        let mut output = [0u8; 32];
+        digest.extract(&mut output).unwrap();
+        assert_eq!(&output[..], spec.2);
+
+        let mut digest = MessageDigest::new(spec.0).unwrap();
+        digest.update(spec.1);
+        let mut long = vec![0u8; 1000];
+        digest.extract(&mut long).unwrap();
+        assert_eq!(&long[..32], spec.2);
+
+        let mut digest = MessageDigest::new(spec.0).unwrap();
+        digest.update(spec.1);
+        let mut reader = digest.into_xof_reader();
+        let mut output = vec![0u8; 1000];
+        for chunk in output.chunks_mut(7) {
+            reader.read_exact(chunk).unwrap();
+        }
+        assert_eq!(output, long);
+    }
+
+    let mut digest = MessageDigest::new(DigestAlgorithm::Sha256).unwrap();
+    assert!(digest.extract(&mut [0u8; 32]).is_err());
+}
+
+#[test]
+fn test_hash_vectored() {
+    use std::io::IoSlice;
+
+    setup();
+
+    let bufs = [IoSlice::new(b"a"), IoSlice::new(b""), IoSlice::new(b"bc")];
+    let d = digest::hash_vectored(DigestAlgorithm::Sha256, DigestFlags::NONE, &bufs, None).unwrap();
+    let expected = b"\xba\x78\x16\xbf\x8f\x01\xcf\xea\x41\x41\x40\xde\x5d\xae\x22\x23\
+                     \xb0\x03\x61\xa3\x96\x17\x7a\x9c\xb4\x10\xff\x61\xf2\x00\x15\xad";
+    assert_eq!(d.algorithm(), DigestAlgorithm::Sha256);
+    assert_eq!(d.as_bytes(), &expected[..]);
+    assert!(d == expected[..]);
+    assert_eq!(
+        format!("{}", d),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        format!("{:X}", d),
+        "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"
+    );
+    let other = digest::hash_vectored(
+        DigestAlgorithm::Sha256,
+        DigestFlags::NONE,
+        &[IoSlice::new(b"abc")],
+        None,
+    )
+    .unwrap();
+    assert_eq!(d, other);
+    let other = digest::hash_vectored(
+        DigestAlgorithm::Sha256,
+        DigestFlags::NONE,
+        &[IoSlice::new(b"abd")],
+        None,
+    )
+    .unwrap();
+    assert_ne!(d, other);
+
+    let d = digest::hash_vectored(DigestAlgorithm::Sha1, DigestFlags::NONE, &[], None).unwrap();
+    assert_eq!(
+        d.as_bytes(),
+        &b"\xda\x39\xa3\xee\x5e\x6b\x4b\x0d\x32\x55\xbf\xef\x95\x60\x18\x90\xaf\xd8\x07\x09"[..]
+    );
+
+    let bufs = [
+        IoSlice::new(b"what do ya want "),
+        IoSlice::new(b"for nothing?"),
+    ];
+    let d = digest::hash_vectored(
+        DigestAlgorithm::Sha256,
+        DigestFlags::NONE,
+        &bufs,
+        Some(b"Jefe"),
+    )
+    .unwrap();
+    assert_eq!(
+        d.as_bytes(),
+        &b"\x5b\xdc\xc1\x46\xbf\x60\x75\x4e\x6a\x04\x24\x26\x08\x95\x75\xc7\
+           \x5a\x00\x3f\x08\x9d\x27\x39\x83\x9d\xec\x58\xb9\x64\xec\x38\x43"[..]
+    );
+    assert_eq!(
+        digest::hash_vectored(DigestAlgorithm::Sha256, DigestFlags::HMAC, &bufs, None).unwrap_err(),
+        Error::INV_ARG
+    );
+    assert_eq!(
+        digest::hash_vectored(DigestAlgorithm::Shake128, DigestFlags::NONE, &bufs, None)
+            .unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+}
+
+#[test]
+fn test_hashing_io() {
+    use std::{
+        cell::Cell,
+        fs,
+        io::{self, Read, Write},
+        rc::Rc,
+    };
+
+    use gcrypt::digest::{HashingReader, HashingWriter};
+
+    setup();
+
+    let data: Vec<u8> = (0..10000).map(|i| (i * 3) as u8).collect();
+    let mut sha256 = [0u8; 32];
+    digest::hash(DigestAlgorithm::Sha256, &data, &mut sha256);
+    let mut sha512 = [0u8; 64];
+    digest::hash(DigestAlgorithm::Sha512, &data, &mut sha512);
+
+    let d = digest::hash_reader(DigestAlgorithm::Sha256, &data[..]).unwrap();
+    assert_eq!(d.as_bytes(), &sha256[..]);
+    assert_eq!(
+        digest::hash_reader(DigestAlgorithm::Shake128, &data[..]).unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+
+    let path = std::env::temp_dir().join(format!("gcrypt-hash-file-{}", std::process::id()));
+    fs::write(&path, &data).unwrap();
+    let d = digest::hash_file(DigestAlgorithm::Sha512, &path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(d.unwrap().as_bytes(), &sha512[..]);
+    assert!(digest::hash_file(DigestAlgorithm::Sha512, &path).is_err());
+
+    let algos = [DigestAlgorithm::Sha256, DigestAlgorithm::Sha512];
+    let progress = Rc::new(Cell::new(0));
+    let mut writer = HashingWriter::with_algorithms(Vec::new(), &algos).unwrap();
+    writer.set_progress({
+        let progress = progress.clone();
+        move |n| progress.set(n)
+    });
+    for chunk in data.chunks(999) {
+        writer.write_all(chunk).unwrap();
+    }
+    assert_eq!(writer.bytes_written(), data.len() as u64);
+    assert_eq!(progress.get(), data.len() as u64);
+    let (output, mut md) = writer.into_parts();
+    assert_eq!(output, data);
+    assert_eq!(
+        md.read_digest(DigestAlgorithm::Sha256).unwrap().as_bytes(),
+        &sha256[..]
+    );
+    assert_eq!(
+        md.read_digest(DigestAlgorithm::Sha512).unwrap().as_bytes(),
+        &sha512[..]
+    );
+    assert_eq!(
+        md.read_digest(DigestAlgorithm::Sha1).unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+
+    progress.set(0);
+    let mut reader = HashingReader::with_algorithms(&data[..], &algos).unwrap();
+    reader.set_progress({
+        let progress = progress.clone();
+        move |n| progress.set(n)
+    });
+    let mut output = Vec::new();
+    io::copy(&mut reader.by_ref().take(5000), &mut output).unwrap();
+    assert_eq!(progress.get(), 5000);
+    reader.read_to_end(&mut output).unwrap();
+    assert_eq!(reader.bytes_read(), data.len() as u64);
+    assert_eq!(progress.get(), data.len() as u64);
+    assert_eq!(output, data);
+    let (_, mut md) = reader.into_parts();
+    assert_eq!(
+        md.read_digest(DigestAlgorithm::Sha512).unwrap().as_bytes(),
+        &sha512[..]
+    );
+
+    assert_eq!(
+        HashingReader::with_algorithms(&data[..], &[]).unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+}
+
+#[test]
+fn test_multi_digest() {
+    use std::io::Write;
+
+    use gcrypt::digest::{Digests, MultiDigest};
+
+    setup();
+
+    let algos = [
+        DigestAlgorithm::Sha256,
+        DigestAlgorithm::Sha1,
+        DigestAlgorithm::Sha256,
+    ];
+    let mut md = MultiDigest::new(algos.iter().cloned()).unwrap();
+    assert_eq!(
+        md.algorithms(),
+        &[DigestAlgorithm::Sha256, DigestAlgorithm::Sha1][..]
+    );
+    md.update(b"a");
+    write!(md, "bc").unwrap();
+    let digests = md.finish().unwrap();
+    assert_eq!(digests.len(), 2);
+    assert!(!digests.contains(DigestAlgorithm::Md5));
+    assert_eq!(
+        digests.get(DigestAlgorithm::Sha1).unwrap().as_bytes(),
+        &b"\xa9\x99\x3e\x36\x47\x06\x81\x6a\xba\x3e\x25\x71\x78\x50\xc2\x6c\x9c\xd0\xd8\x9d"[..]
+    );
+    assert_eq!(
+        digests.get(DigestAlgorithm::Sha256).unwrap().as_bytes(),
+        &b"\xba\x78\x16\xbf\x8f\x01\xcf\xea\x41\x41\x40\xde\x5d\xae\x22\x23\
+           \xb0\x03\x61\xa3\x96\x17\x7a\x9c\xb4\x10\xff\x61\xf2\x00\x15\xad"[..]
+    );
+
+    let text = digests.to_string();
+    assert_eq!(
+        text,
+        "SHA256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad \
+         SHA1:a9993e364706816aba3e25717850c26c9cd0d89d"
+    );
+    let parsed: Digests = text.parse().unwrap();
+    assert_eq!(parsed, digests);
+    let sha1: Digests = "sha1:A9993E364706816ABA3E25717850C26C9CD0D89D"
+        .parse()
+        .unwrap();
+    assert_ne!(sha1, digests);
+    assert!(sha1.matches(&digests));
+    assert!(digests.matches(&sha1));
+    let bad: Digests = "SHA1:a9993e364706816aba3e25717850c26c9cd0d89e"
+        .parse()
+        .unwrap();
+    assert!(!bad.matches(&digests));
+    assert!(!Digests::new().matches(&digests));
+    assert!(!Digests::new().matches(&Digests::new()));
+    assert_eq!(Digests::new(), Digests::new());
+    assert_ne!(Digests::new(), digests);
+    assert_eq!(digests, digests.clone());
+
+    assert_eq!("SHA1".parse::<Digests>().unwrap_err(), Error::INV_DATA);
+    assert_eq!("SHA1:a999".parse::<Digests>().unwrap_err(), Error::INV_DATA);
+    assert_eq!(
+        "FOO:a999".parse::<Digests>().unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+    assert_eq!(
+        "SHA1:a9993e364706816aba3e25717850c26c9cd0d89d SHA1:a9993e364706816aba3e25717850c26c9cd0d89d"
+            .parse::<Digests>()
+            .unwrap_err(),
+        Error::INV_DATA
+    );
+
+    assert_eq!(
+        MultiDigest::new(Vec::new()).unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+    assert_eq!(
+        MultiDigest::new(vec![DigestAlgorithm::Sha1, DigestAlgorithm::Shake128]).unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+}
+
+#[test]
+fn test_sp800_185() {
+    use gcrypt::digest::sp800_185::*;
+
+    setup();
+
+    if !DigestAlgorithm::CShake128.is_available() {
+        assert!(Kmac128::new(b"key", b"").is_err());
+        return;
+    }
+
+    let mut md = MessageDigest::new(DigestAlgorithm::CShake128).unwrap();
+    md.customize(b"", b"Email Signature").unwrap();
+    md.update(b"\x00\x01\x02\x03");
+    let mut output = [0u8; 32];
+    md.extract(&mut output).unwrap();
+    assert_eq!(
+        &output[..],
+        &b"\xc1\xc3\x69\x25\xb6\x40\x9a\x04\xf1\xb5\x04\xfc\xbc\xa9\xd8\x2b\
+           \x40\x17\x27\x7c\xb5\xed\x2b\x20\x65\xfc\x1d\x38\x14\xd5\xaa\xf5"[..]
+    );
+
+    let key: Vec<u8> = (0x40..0x60).collect();
+    let mut kmac = Kmac128::new(&key, b"").unwrap();
+    kmac.update(b"\x00\x01\x02\x03").unwrap();
+    let expected = b"\xe5\x78\x0b\x0d\x3e\xa6\xf7\xd3\xa4\x29\xc5\x70\x6a\xa4\x3a\x00\
+                     \xfa\xdb\xd7\xd4\x96\x28\x83\x9e\x31\x87\x24\x3f\x45\x6e\xe1\x4e";
+    assert_eq!(kmac.get_mac(&mut output), Ok(32));
+    assert_eq!(&output[..], &expected[..]);
+    assert_eq!(kmac.verify(expected), Ok(()));
+    assert_eq!(kmac.verify(&expected[..16]), Err(Error::CHECKSUM));
+    assert_eq!(kmac.verify(&expected[..15]), Err(Error::INV_LENGTH));
+    assert_eq!(kmac.verify(&expected[..1]), Err(Error::INV_LENGTH));
+    kmac.update(b"\x04").unwrap();
+    assert_eq!(kmac.verify(expected), Err(Error::CHECKSUM));
+    kmac.reset().unwrap();
+    kmac.update(b"\x00\x01\x02\x03").unwrap();
+    assert_eq!(kmac.verify(expected), Ok(()));
+
+    let data: Vec<u8> = (0..200).collect();
+    let mut kmac = Kmac256::new(&key, b"My Tagged Application").unwrap();
+    kmac.update(&data).unwrap();
+    let mut output = [0u8; 64];
+    kmac.get_mac(&mut output).unwrap();
+    assert_eq!(
+        &output[..],
+        &b"\xb5\x86\x18\xf7\x1f\x92\xe1\xd5\x6c\x1b\x8c\x55\xdd\xd7\xcd\x18\
+           \x8b\x97\xb4\xca\x4d\x99\x83\x1e\xb2\x69\x9a\x83\x7d\xa2\xe4\xd9\
+           \x70\xfb\xac\xfd\xe5\x00\x33\xae\xa5\x85\xf1\xa2\x70\x85\x10\xc3\
+           \x2d\x07\x88\x08\x01\xbd\x18\x28\x98\xfe\x47\x68\x76\xfc\x89\x65"[..]
+    );
+    assert_eq!(kmac.verify(&output), Ok(()));
+    assert_eq!(kmac.verify(&output[..31]), Err(Error::INV_LENGTH));
+
+    let mut tuple = TupleHash128::new(b"").unwrap();
+    tuple.update(b"\x00\x01\x02").unwrap();
+    tuple.update(b"\x10\x11\x12\x13\x14\x15").unwrap();
+    let mut output = [0u8; 32];
+    tuple.finish(&mut output).unwrap();
+    assert_eq!(
+        &output[..],
+        &b"\xc5\xd8\x78\x6c\x1a\xfb\x9b\x82\x11\x1a\xb3\x4b\x65\xb2\xc0\x04\
+           \x8f\xa6\x4e\x6d\x48\xe2\x63\x26\x4c\xe1\x70\x7d\x3f\xfc\x8e\xd1"[..]
+    );
+
+    let data = b"\x00\x01\x02\x03\x04\x05\x06\x07\x10\x11\x12\x13\x14\x15\x16\x17\
+                 \x20\x21\x22\x23\x24\x25\x26\x27";
+    let expected = b"\xba\x8d\xc1\xd1\xd9\x79\x33\x1d\x3f\x81\x36\x03\xc6\x7f\x72\x60\
+                     \x9a\xb5\xe4\x4b\x94\xa0\xb8\xf9\xaf\x46\x51\x44\x54\xa2\xb4\xf5";
+    let mut parallel = ParallelHash128::new(8, b"").unwrap();
+    parallel.update(&data[..5]).unwrap();
+    parallel.update(&data[5..]).unwrap();
+    parallel.finish(&mut output).unwrap();
+    assert_eq!(&output[..], &expected[..]);
+    let mut parallel = ParallelHash128::new(8, b"").unwrap();
+    parallel.update(data).unwrap();
+    parallel.finish(&mut output).unwrap();
+    assert_eq!(&output[..], &expected[..]);
+    assert!(ParallelHash128::new(0, b"").is_err());
+}
+
+#[test]
+fn test_digest_metadata() {
+    setup();
+
+    let specs: &[(DigestAlgorithm, &str, &[u8])] = &[
+        (
+            DigestAlgorithm::Md5,
+            "1.2.840.113549.2.5",
+            b"\x30\x20\x30\x0c\x06\x08\x2a\x86\x48\x86\xf7\x0d\x02\x05\x05\x00\x04\x10",
+        ),
+        (
+            DigestAlgorithm::Sha1,
+            "1.3.14.3.2.26",
+            b"\x30\x21\x30\x09\x06\x05\x2b\x0e\x03\x02\x1a\x05\x00\x04\x14",
+        ),
+        (
+            DigestAlgorithm::Sha256,
+            "2.16.840.1.101.3.4.2.1",
+            b"\x30\x31\x30\x0d\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x01\x05\x00\x04\x20",
+        ),
+        (
+            DigestAlgorithm::Sha512,
+            "2.16.840.1.101.3.4.2.3",
+            b"\x30\x51\x30\x0d\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x03\x05\x00\x04\x40",
+        ),
+    ];
+    for spec in specs {
+        assert_eq!(spec.0.asn_oid_prefix().as_deref(), Some(spec.2));
+        assert_eq!(spec.0.oid().as_deref(), Some(spec.1));
+        assert_eq!(DigestAlgorithm::from_oid(spec.1), Some(spec.0));
+    }
+    assert_eq!(DigestAlgorithm::Crc32.asn_oid_prefix(), None);
+    assert_eq!(DigestAlgorithm::Crc32.oid(), None);
+    assert_eq!(DigestAlgorithm::from_oid("1.2.3.4"), None);
+    assert_eq!(DigestAlgorithm::from_oid("SHA256"), None);
+    assert_eq!(DigestAlgorithm::from_oid(""), None);
+    assert_eq!(DigestAlgorithm::from_oid("2..16"), None);
+
+    assert_eq!(DigestAlgorithm::Sha256.block_len(), 64);
+    assert_eq!(DigestAlgorithm::Sha512.block_len(), 128);
+    assert_eq!(DigestAlgorithm::Sha3_256.block_len(), 136);
+    assert_eq!(DigestAlgorithm::Shake128.block_len(), 168);
+    assert!(DigestAlgorithm::Shake128.is_xof());
+    assert!(DigestAlgorithm::CShake256.is_xof());
+    assert!(!DigestAlgorithm::Sha3_256.is_xof());
+}
+
+#[test]
+fn test_blake2() {
+    use gcrypt::digest::Blake2;
+
+    setup();
+
+    if !DigestAlgorithm::Blake2B512.is_available() {
+        return;
+    }
+
+    let key: Vec<u8> = (0..64).collect();
+    let tag = Blake2::blake2b().key(&key).hash(b"").unwrap();
+    assert_eq!(tag.algorithm(), DigestAlgorithm::Blake2B512);
+    assert_eq!(
+        tag.as_bytes(),
+        &b"\x10\xeb\xb6\x77\x00\xb1\x86\x8e\xfb\x44\x17\x98\x7a\xcf\x46\x90\
+           \xae\x9d\x97\x2f\xb7\xa5\x90\xc2\xf0\x28\x71\x79\x9a\xaa\x47\x86\
+           \xb5\xe9\x96\xe8\xf0\xf4\xeb\x98\x1f\xc2\x14\xb0\x05\xf4\x2d\x2f\
+           \xf4\x23\x34\x99\x39\x16\x53\xdf\x7a\xef\xcb\xc1\x3f\xc5\x15\x68"[..]
+    );
+    let tag = Blake2::blake2s().key(&key[..32]).hash(b"").unwrap();
+    assert_eq!(
+        tag.as_bytes(),
+        &b"\x48\xa8\x99\x7d\xa4\x07\x87\x6b\x3d\x79\xc0\xd9\x23\x25\xad\x3b\
+           \x89\xcb\xb7\x54\xd8\x6a\xb7\x1a\xee\x04\x7a\xd3\x45\xfd\x2c\x49"[..]
+    );
+
+    let expected = b"\xe2\x3c\x35\x71\x3e\x72\x49\xf3\x69\xb7\xc6\xf6\x02\x91\xc0\xaf\
+                     \x9d\x6a\xc0\x23\x1d\x80\xf4\x6e\x13\xb1\x31\x3f\xe7\xf4\xa4\xd5";
+    let mut blake2 = Blake2::blake2b();
+    blake2.key(b"secret").output_len(32);
+    assert_eq!(blake2.algorithm(), Ok(DigestAlgorithm::Blake2B256));
+    assert_eq!(
+        format!("{:?}", blake2),
+        "Blake2 { wide: true, output_len: 32 }"
+    );
+    assert_eq!(blake2.hash(b"abc").unwrap().as_bytes(), &expected[..]);
+    assert_eq!(blake2.verify(b"abc", expected), Ok(()));
+    assert_eq!(blake2.verify(b"abd", expected), Err(Error::CHECKSUM));
+    let mut md = blake2.open().unwrap();
+    md.update(b"a");
+    md.update(b"bc");
+    assert_eq!(md.get_only_digest(), Some(&expected[..]));
+
+    let tag = Blake2::blake2s()
+        .key(b"secret")
+        .output_len(16)
+        .hash(b"abc")
+        .unwrap();
+    assert_eq!(
+        tag.as_bytes(),
+        &b"\x9a\xf4\xe6\xcc\xbb\xfa\xfb\x7c\x9d\xbc\x60\x88\xca\x27\xf3\xda"[..]
+    );
+
+    assert_eq!(
+        Blake2::blake2b().output_len(31).hash(b"").unwrap_err(),
+        Error::INV_LENGTH
+    );
+    assert_eq!(
+        Blake2::blake2s().key(&key).hash(b"").unwrap_err(),
+        Error::INV_KEYLEN
+    );
+}
+
+#[test]
+fn test_merkle() {
+    use gcrypt::digest::merkle::{self, MerkleTree};
+
+    setup();
+
+    let leaves: &[&[u8]] = &[
+        b"",
+        b"\x00",
+        b"\x10",
+        b"\x20\x21",
+        b"\x30\x31",
+        b"\x40\x41\x42\x43",
+        b"\x50\x51\x52\x53\x54\x55\x56\x57",
+        b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+    ];
+    let roots: &[&[u8]] = &[
+        b"\x6e\x34\x0b\x9c\xff\xb3\x7a\x98\x9c\xa5\x44\xe6\xbb\x78\x0a\x2c\
+          \x78\x90\x1d\x3f\xb3\x37\x38\x76\x85\x11\xa3\x06\x17\xaf\xa0\x1d",
+        b"\xfa\xc5\x42\x03\xe7\xcc\x69\x6c\xf0\xdf\xcb\x42\xc9\x2a\x1d\x9d\
+          \xba\xf7\x0a\xd9\xe6\x21\xf4\xbd\x8d\x98\x66\x2f\x00\xe3\xc1\x25",
+        b"\xae\xb6\xbc\xfe\x27\x4b\x70\xa1\x4f\xb0\x67\xa5\xe5\x57\x82\x64\
+          \xdb\x0f\xa9\xb5\x1a\xf5\xe0\xba\x15\x91\x58\xf3\x29\xe0\x6e\x77",
+        b"\xd3\x7e\xe4\x18\x97\x6d\xd9\x57\x53\xc1\xc7\x38\x62\xb9\x39\x8f\
+          \xa2\xa2\xcf\x9b\x4f\xf0\xfd\xfe\x8b\x30\xcd\x95\x20\x96\x14\xb7",
+        b"\x4e\x3b\xbb\x1f\x7b\x47\x8d\xcf\xe7\x1f\xb6\x31\x63\x15\x19\xa3\
+          \xbc\xa1\x2c\x9a\xef\xca\x16\x12\xbf\xce\x4c\x13\xa8\x62\x64\xd4",
+        b"\x76\xe6\x7d\xad\xbc\xdf\x1e\x10\xe1\xb7\x4d\xdc\x60\x8a\xbd\x2f\
+          \x98\xdf\xb1\x6f\xbc\xe7\x52\x77\xb5\x23\x2a\x12\x7f\x20\x87\xef",
+        b"\xdd\xb8\x9b\xe4\x03\x80\x9e\x32\x57\x50\xd3\xd2\x63\xcd\x78\x92\
+          \x9c\x29\x42\xb7\x94\x2a\x34\xb7\x7e\x12\x2c\x95\x94\xa7\x4c\x8c",
+        b"\x5d\xc9\xda\x79\xa7\x06\x59\xa9\xad\x55\x9c\xb7\x01\xde\xd9\xa2\
+          \xab\x9d\x82\x3a\xad\x2f\x49\x60\xcf\xe3\x70\xef\xf4\x60\x43\x28",
+    ];
+
+    let algo = DigestAlgorithm::Sha256;
+    let mut tree = MerkleTree::new(algo).unwrap();
+    assert!(tree.is_empty());
+    assert_eq!(
+        tree.root().unwrap(),
+        &b"\xe3\xb0\xc4\x42\x98\xfc\x1c\x14\x9a\xfb\xf4\xc8\x99\x6f\xb9\x24\
+           \x27\xae\x41\xe4\x64\x9b\x93\x4c\xa4\x95\x99\x1b\x78\x52\xb8\x55"[..]
+    );
+    tree.push(leaves[0]).unwrap();
+    tree.extend(&leaves[1..]).unwrap();
+    assert_eq!(tree.len(), leaves.len());
+    assert_eq!(
+        tree.leaf_hash(3),
+        Some(&merkle::leaf_hash(algo, leaves[3]).unwrap()[..])
+    );
+    for (i, root) in roots.iter().enumerate() {
+        assert_eq!(tree.root_at(i + 1).unwrap(), *root);
+    }
+    assert_eq!(tree.root().unwrap(), roots[7]);
+    assert!(tree.root_at(9).is_err());
+
+    assert_eq!(
+        tree.inclusion_proof(2, 8).unwrap(),
+        vec![
+            b"\x07\x50\x6a\x85\xfd\x9d\xd2\xf1\x20\xeb\x69\x4f\x86\x01\x1e\x5b\
+              \xb4\x66\x2e\x5c\x41\x5a\x62\x91\x70\x33\xd4\xa9\x62\x44\x87\xe7"
+                .to_vec(),
+            roots[1].to_vec(),
+            b"\x6b\x47\xaa\xf2\x9e\xe3\xc2\xaf\x9a\xf8\x89\xbc\x1f\xb9\x25\x4d\
+              \xab\xd3\x11\x77\xf1\x62\x32\xdd\x6a\xab\x03\x5c\xa3\x9b\xf6\xe4"
+                .to_vec(),
+        ]
+    );
+    assert_eq!(
+        tree.consistency_proof(3, 7).unwrap(),
+        vec![
+            b"\x02\x98\xd1\x22\x90\x6d\xcf\xc1\x08\x92\xcb\x53\xa7\x39\x92\xfc\
+              \x5b\x9f\x49\x3e\xa4\xc9\xba\xdb\x27\xb7\x91\xb4\x12\x7a\x7f\xe7"
+                .to_vec(),
+            b"\x07\x50\x6a\x85\xfd\x9d\xd2\xf1\x20\xeb\x69\x4f\x86\x01\x1e\x5b\
+              \xb4\x66\x2e\x5c\x41\x5a\x62\x91\x70\x33\xd4\xa9\x62\x44\x87\xe7"
+                .to_vec(),
+            roots[1].to_vec(),
+            b"\x83\x7d\xbb\x15\x2e\x9b\x07\x90\x10\x71\x7e\x84\xe8\x65\xda\x4e\
+              \xbc\x0f\xa1\x98\xa8\x06\xd5\x9d\x31\xbf\x15\xac\xce\xf2\x2d\x0e"
+                .to_vec(),
+        ]
+    );
+
+    for size in 1..=leaves.len() {
+        let root = roots[size - 1];
+        for index in 0..size {
+            let hash = tree.leaf_hash(index).unwrap();
+            let proof = tree.inclusion_proof(index, size).unwrap();
+            let (i, n) = (index as u64, size as u64);
+            assert_eq!(
+                merkle::verify_inclusion(algo, hash, i, n, &proof, root),
+                Ok(())
+            );
+            assert!(merkle::verify_inclusion(algo, hash, i, n, &proof, roots[size % 8]).is_err());
+            assert!(merkle::verify_inclusion(algo, hash, i ^ 1, n, &proof, root).is_err());
+            if !proof.is_empty() {
+                assert!(merkle::verify_inclusion(algo, root, i, n, &proof, root).is_err());
+                let mut bad = proof.clone();
+                bad[0][0] ^= 1;
+                assert!(merkle::verify_inclusion(algo, hash, i, n, &bad, root).is_err());
+                assert!(merkle::verify_inclusion(algo, hash, i, n, &proof[1..], root).is_err());
+            }
+        }
+        for old_size in 1..=size {
+            let old_root = roots[old_size - 1];
+            let proof = tree.consistency_proof(old_size, size).unwrap();
+            let (m, n) = (old_size as u64, size as u64);
+            assert_eq!(
+                merkle::verify_consistency(algo, m, n, old_root, root, &proof),
+                Ok(())
+            );
+            if old_size < size {
+                assert!(merkle::verify_consistency(algo, m, n, root, root, &proof).is_err());
+                assert!(
+                    merkle::verify_consistency(algo, m, n, old_root, old_root, &proof).is_err()
+                );
+                let mut bad = proof.clone();
+                bad.last_mut().unwrap()[0] ^= 1;
+                assert!(merkle::verify_consistency(algo, m, n, old_root, root, &bad).is_err());
+                let short = &proof[..(proof.len() - 1)];
+                assert!(merkle::verify_consistency(algo, m, n, old_root, root, short).is_err());
+            }
+        }
+    }
+    assert!(tree.inclusion_proof(8, 8).is_err());
+    assert!(tree.consistency_proof(0, 8).is_err());
+    assert!(tree.consistency_proof(5, 4).is_err());
+    assert_eq!(
+        MerkleTree::new(DigestAlgorithm::Shake128).unwrap_err(),
+        Error::DIGEST_ALGO
+    );
+}
+
+#[cfg(feature = "digest")]
+fn check_mac_traits<M: ::digest::Mac + ::digest::KeyInit>(algo: gcrypt::mac::Algorithm) {
+    if !algo.is_available() {
+        return;
+    }
+
+    let key: Vec<u8> = (0..(M::key_size() as u8)).collect();
+    let data = b"foobar42FOOBAR17foobar";
+    let mut expected = vec![0u8; M::output_size()];
+    let mut mac = gcrypt::mac::Mac::new(algo).unwrap();
+    mac.set_key(&key).unwrap();
+    mac.update(data).unwrap();
+    mac.get_mac(&mut expected).unwrap();
+
+    let mut m = <M as ::digest::Mac>::new_from_slice(&key).unwrap();
+    m.update(&data[..6]);
+    m.update(&data[6..]);
+    assert_eq!(&m.finalize().into_bytes()[..], &expected[..]);
+    let m = <M as ::digest::Mac>::new_from_slice(&key).unwrap();
+    assert!(m.chain_update(data).verify_slice(&expected).is_ok());
+    let m = <M as ::digest::Mac>::new_from_slice(&key).unwrap();
+    assert!(m.chain_update(&data[1..]).verify_slice(&expected).is_err());
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn test_mac_traits() {
+    use ::digest::{crypto_common::KeyIvInit, FixedOutputReset, KeyInit, Update};
+    use gcrypt::mac::{self, Algorithm as MacAlgorithm};
+
+    setup();
+
+    check_mac_traits::<mac::HmacMd5>(MacAlgorithm::HmacMd5);
+    check_mac_traits::<mac::HmacSha1>(MacAlgorithm::HmacSha1);
+    check_mac_traits::<mac::HmacSha256>(MacAlgorithm::HmacSha256);
+    check_mac_traits::<mac::HmacSha384>(MacAlgorithm::HmacSha384);
+    check_mac_traits::<mac::HmacSha512>(MacAlgorithm::HmacSha512);
+    check_mac_traits::<mac::HmacSha3_256>(MacAlgorithm::HmacSha3_256);
+    check_mac_traits::<mac::HmacSha3_512>(MacAlgorithm::HmacSha3_512);
+    check_mac_traits::<mac::HmacBlake2b512>(MacAlgorithm::HmacBlake2b512);
+    check_mac_traits::<mac::HmacSm3>(MacAlgorithm::HmacSm3);
+    check_mac_traits::<mac::CmacAes128>(MacAlgorithm::CmacAes);
+    check_mac_traits::<mac::CmacAes192>(MacAlgorithm::CmacAes);
+    check_mac_traits::<mac::CmacAes256>(MacAlgorithm::CmacAes);
+    check_mac_traits::<mac::CmacCamellia128>(MacAlgorithm::CmacCamellia);
+    check_mac_traits::<mac::CmacSm4>(MacAlgorithm::CmacSm4);
+
+    let expected = b"\x5b\xdc\xc1\x46\xbf\x60\x75\x4e\x6a\x04\x24\x26\x08\x95\x75\xc7\
+                     \x5a\x00\x3f\x08\x9d\x27\x39\x83\x9d\xec\x58\xb9\x64\xec\x38\x43";
+    let mut hmac = <mac::HmacSha256 as ::digest::Mac>::new_from_slice(b"Jefe").unwrap();
+    hmac.update(b"what do ya want for nothing?");
+    assert_eq!(&hmac.finalize_fixed_reset()[..], &expected[..]);
+    hmac.update(b"what do ya want for nothing?");
+    assert_eq!(hmac.verify_slice(expected), Ok(()));
+    let hmac = mac::HmacSha256::new_from_slice(b"Jefe")
+        .unwrap()
+        .chain(b"what do ya want for nothing?");
+    assert!(hmac.verify_slice(&expected[..16]).is_err());
+    assert!(mac::CmacAes128::new_from_slice(&[0; 15]).is_err());
+
+    let key = [0x42u8; 16];
+    let nonce = [0x24u8; 12];
+    let mut expected = [0u8; 16];
+    let mut gmac = gcrypt::mac::Mac::new(MacAlgorithm::GmacAes).unwrap();
+    gmac.set_key(key).unwrap();
+    gmac.set_iv(nonce).unwrap();
+    gmac.update(b"header").unwrap();
+    gmac.get_mac(&mut expected).unwrap();
+    let gmac = mac::GmacAes128::new(&key.into(), &nonce.into()).chain(b"header");
+    assert_eq!(gmac.verify_slice(&expected), Ok(()));
+
+    let key = [0x42u8; 32];
+    let nonce = [0x24u8; 16];
+    let mut poly1305 = gcrypt::mac::Mac::new(MacAlgorithm::Poly1305Aes).unwrap();
+    poly1305.set_key(key).unwrap();
+    poly1305.set_iv(nonce).unwrap();
+    poly1305.update(b"message").unwrap();
+    poly1305.get_mac(&mut expected).unwrap();
+    let poly1305 = mac::Poly1305Aes::new(&key.into(), &nonce.into()).chain(b"message");
+    assert_eq!(
+        &::digest::Mac::finalize(poly1305).into_bytes()[..],
+        &expected[..]
+    );
+}
+
+#[test]
+fn test_mac_oneshot() {
+    use std::io::IoSlice;
+
+    use gcrypt::mac::{self, Algorithm as MacAlgorithm};
+
+    setup();
+
+    let algo = MacAlgorithm::HmacSha256;
+    let data = b"what do ya want for nothing?";
+    let expected = b"\x5b\xdc\xc1\x46\xbf\x60\x75\x4e\x6a\x04\x24\x26\x08\x95\x75\xc7\
+                     \x5a\x00\x3f\x08\x9d\x27\x39\x83\x9d\xec\x58\xb9\x64\xec\x38\x43";
+    let tag = mac::compute(algo, b"Jefe", data).unwrap();
+    assert_eq!(tag.algorithm(), algo);
+    assert_eq!(tag.as_bytes(), &expected[..]);
+    assert!(tag == expected[..]);
+    assert_eq!(
+        tag.to_string(),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+    assert_eq!(
+        format!("{:X}", tag),
+        "5BDCC146BF60754E6A042426089575C75A003F089D2739839DEC58B964EC3843"
+    );
+    assert_eq!(
+        tag.to_base64(),
+        "W9zBRr9gdU5qBCQmCJV1x1oAPwidJzmDnexYuWTsOEM="
+    );
+
+    let bufs = [IoSlice::new(&data[..10]), IoSlice::new(&data[10..])];
+    let other = mac::compute_vectored(algo, b"Jefe", None, &bufs).unwrap();
+    assert_eq!(tag, other);
+    assert_ne!(tag, mac::compute(algo, b"Jeff", data).unwrap());
+
+    assert_eq!(mac::verify(algo, b"Jefe", data, expected), Ok(()));
+    assert_eq!(
+        mac::verify_vectored(algo, b"Jefe", None, &bufs, expected),
+        Ok(())
+    );
+    assert_eq!(
+        mac::verify(algo, b"Jefe", data, &expected[..16]),
+        Err(Error::INV_LENGTH)
+    );
+    assert_eq!(
+        mac::verify(algo, b"Jefe", data, &expected[..1]),
+        Err(Error::INV_LENGTH)
+    );
+    assert_eq!(
+        mac::verify(algo, b"Jefe", &data[1..], expected).map_err(|e| e.code()),
+        Err(Error::CHECKSUM.code())
+    );
+    assert_eq!(
+        mac::verify(algo, b"Jefe", data, b""),
+        Err(Error::INV_LENGTH)
+    );
+
+    let mut truncated = tag.clone();
+    truncated.truncate(16);
+    assert_eq!(truncated.len(), 16);
+    assert_ne!(truncated, tag);
+    assert_eq!(
+        mac::verify_truncated(algo, b"Jefe", data, truncated.as_bytes(), 16),
+        Ok(())
+    );
+    assert_eq!(
+        mac::verify_truncated(algo, b"Jefe", data, truncated.as_bytes(), 20),
+        Err(Error::INV_LENGTH)
+    );
+    assert_eq!(
+        mac::verify_truncated(algo, b"Jefe", &data[1..], truncated.as_bytes(), 16)
+            .map_err(|e| e.code()),
+        Err(Error::CHECKSUM.code())
+    );
+    assert_eq!(
+        mac::verify_truncated(algo, b"Jefe", data, expected, 0),
+        Ok(())
+    );
+    assert_eq!(
+        mac::verify_truncated(algo, b"Jefe", data, &expected[..9], 0),
+        Err(Error::INV_LENGTH)
+    );
+    truncated.truncate(3);
+    assert_eq!(truncated.to_base64(), "W9zB");
+    truncated.truncate(2);
+    assert_eq!(truncated.to_base64(), "W9w=");
+    truncated.truncate(1);
+    assert_eq!(truncated.to_base64(), "Ww==");
+
+    let key = [0x42u8; 16];
+    let nonce = [0x24u8; 12];
+    let mut gmac = mac::Mac::new(MacAlgorithm::GmacAes).unwrap();
+    gmac.set_key(key).unwrap();
+    gmac.set_iv(nonce).unwrap();
+    gmac.update(b"header").unwrap();
+    let mut expected = [0u8; 16];
+    gmac.get_mac(&mut expected).unwrap();
+    let bufs = [IoSlice::new(b"head"), IoSlice::new(b"er")];
+    let tag = mac::compute_vectored(MacAlgorithm::GmacAes, &key, Some(&nonce), &bufs).unwrap();
+    assert_eq!(tag.as_bytes(), &expected[..]);
+    assert_eq!(
+        mac::verify_vectored(MacAlgorithm::GmacAes, &key, Some(&nonce), &bufs, &expected),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_otp() {
+    use gcrypt::mac::{
+        otp::{Hotp, OtpAuth, Totp},
+        Algorithm as MacAlgorithm,
+    };
+
+    setup();
+
+    let secret = b"12345678901234567890";
+    let hotp = Hotp::new(MacAlgorithm::HmacSha1, secret).unwrap();
+    let expected = [
+        "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871",
+        "520489",
+    ];
+    for (counter, code) in expected.iter().enumerate() {
+        assert_eq!(hotp.generate(counter as u64).unwrap(), *code);
+    }
+    assert_eq!(hotp.verify("969429", 0, 5), Ok(3));
+    assert_eq!(hotp.verify("969429", 4, 5), Err(Error::CHECKSUM));
+    assert_eq!(hotp.verify("969429", 0, 2), Err(Error::CHECKSUM));
+    assert_eq!(hotp.verify("969429", 0, 10), Ok(3));
+    assert_eq!(hotp.verify("969429", 0, 11), Err(Error::INV_VALUE));
+    assert_eq!(hotp.verify("969429", 0, u64::MAX), Err(Error::INV_VALUE));
+    assert_eq!(
+        Hotp::new(MacAlgorithm::HmacMd5, secret).err(),
+        Some(Error::MAC_ALGO)
+    );
+    assert_eq!(
+        Hotp::new(MacAlgorithm::HmacSha1, &secret[..15]).err(),
+        Some(Error::INV_KEYLEN)
+    );
+    assert_eq!(
+        Totp::new(MacAlgorithm::HmacSha1, b"").err(),
+        Some(Error::INV_KEYLEN)
+    );
+
+    let seeds: [(MacAlgorithm, &[u8]); 3] = [
+        (MacAlgorithm::HmacSha1, b"12345678901234567890"),
+        (
+            MacAlgorithm::HmacSha256,
+            b"12345678901234567890123456789012",
         ),
         (
-            DigestAlgorithm::Stribog256,
-            b"\xd1\xe5\x20\xe2\xe5\xf2\xf0\xe8\x2c\x20\xd1\xf2\xf0\xe8\xe1\xee\xe6\xe8\
-           \x20\xe2\xed\xf3\xf6\xe8\x2c\x20\xe2\xe5\xfe\xf2\xfa\x20\xf1\x20\xec\xee\
-           \xf0\xff\x20\xf1\xf2\xf0\xe5\xeb\xe0\xec\xe8\x20\xed\xe0\x20\xf5\xf0\xe0\
-           \xe1\xf0\xfb\xff\x20\xef\xeb\xfa\xea\xfb\x20\xc8\xe3\xee\xf0\xe5\xe2\xfb",
-            b"\x9d\xd2\xfe\x4e\x90\x40\x9e\x5d\xa8\x7f\x53\x97\x6d\x74\x05\xb0\
-           \xc0\xca\xc6\x28\xfc\x66\x9a\x74\x1d\x50\x06\x3c\x55\x7e\x8f\x50",
+            MacAlgorithm::HmacSha512,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
         ),
     ];
+    let vectors: [(u64, [&str; 3]); 6] = [
+        (59, ["94287082", "46119246", "90693936"]),
+        (1111111109, ["07081804", "68084774", "25091201"]),
+        (1111111111, ["14050471", "67062674", "99943326"]),
+        (1234567890, ["89005924", "91819424", "93441116"]),
+        (2000000000, ["69279037", "90698825", "38618901"]),
+        (20000000000, ["65353130", "77737706", "47863826"]),
+    ];
+    for (i, &(algo, seed)) in seeds.iter().enumerate() {
+        let mut totp = Totp::new(algo, seed).unwrap();
+        totp.digits(8).unwrap();
+        for &(time, codes) in &vectors {
+            assert_eq!(totp.generate_at(time).unwrap(), codes[i]);
+            assert_eq!(totp.verify_at(codes[i], time), Ok(time / 30));
+        }
+    }
 
-    for spec in specs {
-        if !spec.0.is_available() {
-            continue;
+    let mut totp = Totp::new(MacAlgorithm::HmacSha1, secret).unwrap();
+    let code = totp.generate_at(1111111111).unwrap();
+    assert_eq!(totp.verify_at(&code, 1111111111 - 30), Ok(37037037));
+    assert_eq!(totp.verify_at(&code, 1111111111 + 30), Ok(37037037));
+    assert_eq!(totp.verify_at(&code, 1111111111 + 60), Err(Error::CHECKSUM));
+    assert_eq!(totp.window(0, 11).err(), Some(Error::INV_VALUE));
+    assert_eq!(
+        totp.window(u64::MAX, u64::MAX).err(),
+        Some(Error::INV_VALUE)
+    );
+    totp.window(2, 0).unwrap();
+    assert_eq!(totp.verify_at(&code, 1111111111 + 60), Ok(37037037));
+    assert_eq!(totp.verify_at(&code, 1111111111 - 30), Err(Error::CHECKSUM));
+    totp.t0(1000).step(60).unwrap();
+    assert_eq!(totp.counter_at(1000 + 119), Ok(1));
+    assert_eq!(totp.counter_at(999), Err(Error::INV_VALUE));
+    assert_eq!(totp.step(0).err(), Some(Error::INV_VALUE));
+    assert_eq!(totp.digits(5).err(), Some(Error::INV_VALUE));
+    let now = Totp::new(MacAlgorithm::HmacSha1, secret).unwrap();
+    assert_eq!(now.verify(&now.generate().unwrap()).map(|_| ()), Ok(()));
+
+    let uri = hotp.to_uri("Example:alice@example.com", Some("Example Co"), 7);
+    assert_eq!(
+        uri,
+        "otpauth://hotp/Example:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+         &issuer=Example%20Co&algorithm=SHA1&digits=6&counter=7"
+    );
+    match OtpAuth::parse(&uri).unwrap() {
+        OtpAuth::Hotp {
+            hotp: parsed,
+            counter,
+            ref label,
+            ref issuer,
+        } => {
+            assert_eq!(counter, 7);
+            assert_eq!(label, "Example:alice@example.com");
+            assert_eq!(issuer.as_deref(), Some("Example Co"));
+            assert_eq!(parsed.secret(), &secret[..]);
+            assert_eq!(parsed.generate(7).unwrap(), "162583");
         }
+        _ => panic!("expected hotp"),
+    }
 
-        check_digest(spec.0, spec.1, spec.2);
+    let uri = "otpauth://totp/ACME%20Co:john?secret=gezdgnbvgy3tqojqgezdgnbvgy3tqojq====\
+               &algorithm=SHA256&digits=8&period=60&issuer=ACME%20Co";
+    let parsed = OtpAuth::parse(uri).unwrap();
+    match parsed {
+        OtpAuth::Totp {
+            ref totp,
+            ref label,
+            ref issuer,
+        } => {
+            assert_eq!(label, "ACME Co:john");
+            assert_eq!(issuer.as_deref(), Some("ACME Co"));
+            assert_eq!(totp.time_step(), 60);
+            assert_eq!(totp.hotp().algorithm(), MacAlgorithm::HmacSha256);
+            assert_eq!(totp.hotp().digit_count(), 8);
+            assert_eq!(totp.hotp().secret(), &secret[..]);
+        }
+        _ => panic!("expected totp"),
+    }
+    assert_eq!(
+        parsed.to_uri(),
+        "otpauth://totp/ACME%20Co:john?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+         &issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60"
+    );
+
+    for uri in [
+        "http://totp/x?secret=GEZA",
+        "otpauth://totp/x",
+        "otpauth://motp/x?secret=GEZA",
+        "otpauth://hotp/x?secret=GEZA",
+        "otpauth://totp/x%2?secret=GEZA",
+    ] {
+        assert_eq!(OtpAuth::parse(uri).err(), Some(Error::INV_URI), "{}", uri);
     }
+    assert_eq!(
+        OtpAuth::parse("otpauth://totp/x?secret=GE1A").err(),
+        Some(Error::INV_VALUE)
+    );
+    assert_eq!(
+        OtpAuth::parse("otpauth://totp/x?secret=GEZA").err(),
+        Some(Error::INV_KEYLEN)
+    );
+    assert_eq!(
+        OtpAuth::parse("otpauth://hotp/x?secret=GEZDGNBVGY3TQOJQGEZDGNA&counter=0").err(),
+        Some(Error::INV_KEYLEN)
+    );
+    assert_eq!(
+        OtpAuth::parse("otpauth://totp/x?secret=GEZA&algorithm=MD5").err(),
+        Some(Error::MAC_ALGO)
+    );
+}
+
+#[cfg(feature = "digest")]
+fn check_digest_traits<D: ::digest::Digest + Clone>(algo: DigestAlgorithm) {
+    if !algo.is_available() {
+        return;
+    }
+
+    let data = b"foobar42FOOBAR17foobar";
+    let mut expected = vec![0u8; algo.digest_len()];
+    digest::hash(algo, data, &mut expected);
+    assert_eq!(<D as ::digest::Digest>::output_size(), expected.len());
+
+    let mut d = D::new();
+    d.update(&data[..6]);
+    let copy = d.clone();
+    d.update(&data[6..]);
+    assert_eq!(&d.finalize()[..], &expected[..]);
+    assert_eq!(&D::digest(data)[..], &expected[..]);
+    assert_eq!(&copy.chain_update(&data[6..]).finalize()[..], &expected[..]);
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn test_digest_traits() {
+    use ::digest::{ExtendableOutput, Update, XofReader};
+    use gcrypt::digest::*;
+
+    setup();
+
+    check_digest_traits::<Md5>(DigestAlgorithm::Md5);
+    check_digest_traits::<Sha1>(DigestAlgorithm::Sha1);
+    check_digest_traits::<Rmd160>(DigestAlgorithm::Rmd160);
+    check_digest_traits::<Sha224>(DigestAlgorithm::Sha224);
+    check_digest_traits::<Sha256>(DigestAlgorithm::Sha256);
+    check_digest_traits::<Sha384>(DigestAlgorithm::Sha384);
+    check_digest_traits::<Sha512>(DigestAlgorithm::Sha512);
+    check_digest_traits::<Sha512_224>(DigestAlgorithm::Sha512_224);
+    check_digest_traits::<Sha512_256>(DigestAlgorithm::Sha512_256);
+    check_digest_traits::<Sha3_224>(DigestAlgorithm::Sha3_224);
+    check_digest_traits::<Sha3_256>(DigestAlgorithm::Sha3_256);
+    check_digest_traits::<Sha3_384>(DigestAlgorithm::Sha3_384);
+    check_digest_traits::<Sha3_512>(DigestAlgorithm::Sha3_512);
+    check_digest_traits::<Blake2b256>(DigestAlgorithm::Blake2B256);
+    check_digest_traits::<Blake2b384>(DigestAlgorithm::Blake2B384);
+    check_digest_traits::<Blake2b512>(DigestAlgorithm::Blake2B512);
+    check_digest_traits::<Blake2s128>(DigestAlgorithm::Blake2S128);
+    check_digest_traits::<Blake2s256>(DigestAlgorithm::Blake2S256);
+    check_digest_traits::<Stribog256>(DigestAlgorithm::Stribog256);
+    check_digest_traits::<Stribog512>(DigestAlgorithm::Stribog512);
+    check_digest_traits::<Whirlpool>(DigestAlgorithm::Whirlpool);
+    check_digest_traits::<Sm3>(DigestAlgorithm::Sm3);
+
+    let mut d = Sha256::default();
+    Update::update(&mut d, b"foo");
+    let first = ::digest::Digest::finalize_reset(&mut d);
+    Update::update(&mut d, b"foo");
+    assert_eq!(::digest::Digest::finalize(d), first);
+
+    let mut xof = Shake128::default();
+    xof.update(b"");
+    let mut output = [0u8; 32];
+    xof.finalize_xof().read(&mut output);
+    assert_eq!(
+        &output[..],
+        &b"\x7f\x9c\x2b\xa4\xe8\x8f\x82\x7d\x61\x60\x45\x50\x76\x05\x85\x3e\
+           \xd7\x3b\x80\x93\xf6\xef\xbc\x88\xeb\x1a\x6e\xac\xfa\x66\xef\x26"[..]
+    );
+    let mut output = [0u8; 32];
+    Shake256::default()
+        .chain(b"")
+        .finalize_xof_into(&mut output);
+    assert_eq!(
+        &output[..],
+        &b"\x46\xb9\xdd\x2b\x0b\xa8\x8d\x13\x23\x3b\x3f\xeb\x74\x3e\xeb\x24\
+           \x3f\xcd\x52\xea\x62\xb8\x1b\x82\xb5\x0c\x27\x64\x6e\xd5\x76\x2f"[..]
+    );
 }
 
 fn check_hmac(algo: DigestAlgorithm, data: &[u8], key: &[u8], expected: &[u8]) {
@@ -1743,6 +3838,225 @@ fn check_scrypt() {
     }
 }
 
+fn check_hkdf() {
+    // (digest, ikm, salt, info, prk, okm)
+    type HkdfVector<'a> = (
+        DigestAlgorithm,
+        &'a [u8],
+        Option<&'a [u8]>,
+        &'a [u8],
+        &'a [u8],
+        &'a [u8],
+    );
+
+    let test_vectors: &[HkdfVector<'_>] = &[
+        (
+            DigestAlgorithm::Sha256,
+            b"\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\
+           \x0b\x0b\x0b\x0b\x0b\x0b",
+            Some(b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c"),
+            b"\xf0\xf1\xf2\xf3\xf4\xf5\xf6\xf7\xf8\xf9",
+            b"\x07\x77\x09\x36\x2c\x2e\x32\xdf\x0d\xdc\x3f\x0d\xc4\x7b\xba\x63\
+           \x90\xb6\xc7\x3b\xb5\x0f\x9c\x31\x22\xec\x84\x4a\xd7\xc2\xb3\xe5",
+            b"\x3c\xb2\x5f\x25\xfa\xac\xd5\x7a\x90\x43\x4f\x64\xd0\x36\x2f\x2a\
+           \x2d\x2d\x0a\x90\xcf\x1a\x5a\x4c\x5d\xb0\x2d\x56\xec\xc4\xc5\xbf\
+           \x34\x00\x72\x08\xd5\xb8\x87\x18\x58\x65",
+        ),
+        (
+            DigestAlgorithm::Sha256,
+            b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f\
+           \x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1a\x1b\x1c\x1d\x1e\x1f\
+           \x20\x21\x22\x23\x24\x25\x26\x27\x28\x29\x2a\x2b\x2c\x2d\x2e\x2f\
+           \x30\x31\x32\x33\x34\x35\x36\x37\x38\x39\x3a\x3b\x3c\x3d\x3e\x3f\
+           \x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4a\x4b\x4c\x4d\x4e\x4f",
+            Some(
+                b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f\
+                \x70\x71\x72\x73\x74\x75\x76\x77\x78\x79\x7a\x7b\x7c\x7d\x7e\x7f\
+                \x80\x81\x82\x83\x84\x85\x86\x87\x88\x89\x8a\x8b\x8c\x8d\x8e\x8f\
+                \x90\x91\x92\x93\x94\x95\x96\x97\x98\x99\x9a\x9b\x9c\x9d\x9e\x9f\
+                \xa0\xa1\xa2\xa3\xa4\xa5\xa6\xa7\xa8\xa9\xaa\xab\xac\xad\xae\xaf",
+            ),
+            b"\xb0\xb1\xb2\xb3\xb4\xb5\xb6\xb7\xb8\xb9\xba\xbb\xbc\xbd\xbe\xbf\
+           \xc0\xc1\xc2\xc3\xc4\xc5\xc6\xc7\xc8\xc9\xca\xcb\xcc\xcd\xce\xcf\
+           \xd0\xd1\xd2\xd3\xd4\xd5\xd6\xd7\xd8\xd9\xda\xdb\xdc\xdd\xde\xdf\
+           \xe0\xe1\xe2\xe3\xe4\xe5\xe6\xe7\xe8\xe9\xea\xeb\xec\xed\xee\xef\
+           \xf0\xf1\xf2\xf3\xf4\xf5\xf6\xf7\xf8\xf9\xfa\xfb\xfc\xfd\xfe\xff",
+            b"\x06\xa6\xb8\x8c\x58\x53\x36\x1a\x06\x10\x4c\x9c\xeb\x35\xb4\x5c\
+           \xef\x76\x00\x14\x90\x46\x71\x01\x4a\x19\x3f\x40\xc1\x5f\xc2\x44",
+            b"\xb1\x1e\x39\x8d\xc8\x03\x27\xa1\xc8\xe7\xf7\x8c\x59\x6a\x49\x34\
+           \x4f\x01\x2e\xda\x2d\x4e\xfa\xd8\xa0\x50\xcc\x4c\x19\xaf\xa9\x7c\
+           \x59\x04\x5a\x99\xca\xc7\x82\x72\x71\xcb\x41\xc6\x5e\x59\x0e\x09\
+           \xda\x32\x75\x60\x0c\x2f\x09\xb8\x36\x77\x93\xa9\xac\xa3\xdb\x71\
+           \xcc\x30\xc5\x81\x79\xec\x3e\x87\xc1\x4c\x01\xd5\xc1\xf3\x43\x4f\
+           \x1d\x87",
+        ),
+        (
+            DigestAlgorithm::Sha256,
+            b"\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\
+           \x0b\x0b\x0b\x0b\x0b\x0b",
+            Some(b""),
+            b"",
+            b"\x19\xef\x24\xa3\x2c\x71\x7b\x16\x7f\x33\xa9\x1d\x6f\x64\x8b\xdf\
+           \x96\x59\x67\x76\xaf\xdb\x63\x77\xac\x43\x4c\x1c\x29\x3c\xcb\x04",
+            b"\x8d\xa4\xe7\x75\xa5\x63\xc1\x8f\x71\x5f\x80\x2a\x06\x3c\x5a\x31\
+           \xb8\xa1\x1f\x5c\x5e\xe1\x87\x9e\xc3\x45\x4e\x5f\x3c\x73\x8d\x2d\
+           \x9d\x20\x13\x95\xfa\xa4\xb6\x1a\x96\xc8",
+        ),
+        (
+            DigestAlgorithm::Sha1,
+            b"\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b",
+            Some(b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c"),
+            b"\xf0\xf1\xf2\xf3\xf4\xf5\xf6\xf7\xf8\xf9",
+            b"\x9b\x6c\x18\xc4\x32\xa7\xbf\x8f\x0e\x71\xc8\xeb\x88\xf4\xb3\x0b\
+           \xaa\x2b\xa2\x43",
+            b"\x08\x5a\x01\xea\x1b\x10\xf3\x69\x33\x06\x8b\x56\xef\xa5\xad\x81\
+           \xa4\xf1\x4b\x82\x2f\x5b\x09\x15\x68\xa9\xcd\xd4\xf1\x55\xfd\xa2\
+           \xc2\x2e\x42\x24\x78\xd3\x05\xf3\xf8\x96",
+        ),
+        (
+            DigestAlgorithm::Sha1,
+            b"\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\x0c\
+           \x0c\x0c\x0c\x0c\x0c\x0c",
+            None,
+            b"",
+            b"\x2a\xdc\xca\xda\x18\x77\x9e\x7c\x20\x77\xad\x2e\xb1\x9d\x3f\x3e\
+           \x73\x13\x85\xdd",
+            b"\x2c\x91\x11\x72\x04\xd7\x45\xf3\x50\x0d\x63\x6a\x62\xf6\x4f\x0a\
+           \xb3\xba\xe5\x48\xaa\x53\xd4\x23\xb0\xd1\xf2\x7e\xbb\xa6\xf5\xe5\
+           \x67\x3a\x08\x1d\x70\xcc\xe7\xac\xfc\x48",
+        ),
+    ];
+
+    let mut prk = [0u8; 32];
+    let mut key = [0u8; 82];
+    for tv in test_vectors {
+        let prk = &mut prk[..tv.4.len()];
+        let key = &mut key[..tv.5.len()];
+        kdf::hkdf_extract(tv.0, tv.1, tv.2, prk).unwrap();
+        assert_eq!(tv.4, &prk[..]);
+        kdf::hkdf_expand(tv.0, prk, tv.3, key).unwrap();
+        assert_eq!(tv.5, &key[..]);
+        key.iter_mut().for_each(|b| *b = 0);
+        kdf::hkdf(tv.0, tv.1, tv.2, tv.3, key).unwrap();
+        assert_eq!(tv.5, &key[..]);
+    }
+
+    // From libgcrypt 1.11 `hkdf` uses the native implementation; it must agree with the
+    // separate HMAC-based steps for every combination of inputs.
+    let long: Vec<u8> = (0..200).map(|i| i as u8).collect();
+    for &digest in &[DigestAlgorithm::Sha256, DigestAlgorithm::Sha512] {
+        let len = digest.digest_len();
+        for &secret in &[&b""[..], b"ikm", &long[..]] {
+            for &salt in &[None, Some(&b""[..]), Some(b"salt"), Some(&long[..])] {
+                for &info in &[&b""[..], b"info", &long[..]] {
+                    for &out_len in &[1, len, len + 1, 255 * len] {
+                        let mut prk = vec![0u8; len];
+                        let mut expected = vec![0u8; out_len];
+                        kdf::hkdf_extract(digest, secret, salt, &mut prk).unwrap();
+                        kdf::hkdf_expand(digest, &prk, info, &mut expected).unwrap();
+                        let mut key = vec![0u8; out_len];
+                        kdf::hkdf(digest, secret, salt, info, &mut key).unwrap();
+                        assert_eq!(key, expected);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut key = vec![0u8; 255 * 32];
+    assert_eq!(
+        kdf::hkdf(DigestAlgorithm::Sha256, b"secret", None, b"", &mut key),
+        Ok(())
+    );
+    key.push(0);
+    assert_eq!(
+        kdf::hkdf(DigestAlgorithm::Sha256, b"secret", None, b"", &mut key),
+        Err(Error::INV_LENGTH)
+    );
+    assert_eq!(
+        kdf::hkdf_expand(DigestAlgorithm::Sha256, &prk, b"", &mut key),
+        Err(Error::INV_LENGTH)
+    );
+    assert_eq!(
+        kdf::hkdf_expand(DigestAlgorithm::Sha256, &prk[..16], b"", &mut key[..32]),
+        Err(Error::INV_LENGTH)
+    );
+    assert_eq!(
+        kdf::hkdf_extract(DigestAlgorithm::Sha256, b"secret", None, &mut prk[..20]),
+        Err(Error::INV_LENGTH)
+    );
+    assert_eq!(
+        kdf::hkdf(DigestAlgorithm::Crc32, b"secret", None, b"", &mut key[..4]),
+        Err(Error::DIGEST_ALGO)
+    );
+}
+
+fn check_argon2() {
+    use gcrypt::kdf::{Argon2, Argon2Variant, Executor, Job, ThreadExecutor};
+
+    let test_vectors: &[(Argon2Variant, &[u8])] = &[
+        (
+            Argon2Variant::Argon2d,
+            b"\x51\x2b\x39\x1b\x6f\x11\x62\x97\x53\x71\xd3\x09\x19\x73\x42\x94\
+           \xf8\x68\xe3\xbe\x39\x84\xf3\xc1\xa1\x3a\x4d\xb9\xfa\xbe\x4a\xcb",
+        ),
+        (
+            Argon2Variant::Argon2i,
+            b"\xc8\x14\xd9\xd1\xdc\x7f\x37\xaa\x13\xf0\xd7\x7f\x24\x94\xbd\xa1\
+           \xc8\xde\x6b\x01\x6d\xd3\x88\xd2\x99\x52\xa4\xc4\x67\x2b\x6c\xe8",
+        ),
+        (
+            Argon2Variant::Argon2id,
+            b"\x0d\x64\x0d\xf5\x8d\x78\x76\x6c\x08\xc0\x37\xa3\x4a\x8b\x53\xc9\
+           \xd0\x1e\xf0\x45\x2d\x75\xb6\x5e\xb5\x25\x20\xe9\x6b\x01\xe6\x59",
+        ),
+    ];
+
+    let password = [0x01u8; 32];
+    let salt = [0x02u8; 16];
+    let inline = |job: Job| job.run();
+    let executors: [Option<&dyn Executor>; 3] = [None, Some(&ThreadExecutor), Some(&inline)];
+    for tv in test_vectors {
+        for executor in &executors {
+            let mut argon2 = Argon2::new(tv.0);
+            argon2
+                .t_cost(3)
+                .m_cost(32)
+                .parallelism(4)
+                .secret(&[0x03; 8])
+                .associated_data(&[0x04; 12]);
+            if let Some(executor) = executor {
+                argon2.executor(*executor);
+            }
+            let mut key = [0u8; 32];
+            argon2.derive(&password, &salt, &mut key).unwrap();
+            assert_eq!(tv.1, &key[..]);
+        }
+    }
+
+    let mut key = [0u8; 32];
+    let mut argon2 = Argon2::new(Argon2Variant::Argon2id);
+    assert_eq!(
+        argon2.derive(&password, &salt, &mut key[..3]),
+        Err(Error::INV_LENGTH)
+    );
+    argon2.m_cost(31);
+    assert_eq!(
+        argon2.derive(&password, &salt, &mut key),
+        Err(Error::INV_VALUE)
+    );
+    argon2.m_cost(32).parallelism(0);
+    assert_eq!(
+        argon2.derive(&password, &salt, &mut key),
+        Err(Error::INV_VALUE)
+    );
+    argon2.parallelism(1).t_cost(0);
+    assert_eq!(
+        argon2.derive(&password, &salt, &mut key),
+        Err(Error::INV_VALUE)
+    );
+}
+
 #[test]
 fn test_kdfs() {
     let token = setup();
@@ -1752,6 +4066,10 @@ fn test_kdfs() {
     if token.check_version("1.6.0") {
         check_scrypt();
     }
+    check_hkdf();
+    if token.check_version("1.10.0") {
+        check_argon2();
+    }
 }
 
 const FLAG_CRYPT: usize = 1;